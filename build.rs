@@ -0,0 +1,68 @@
+//! Build script embedding the AWS IAM actions catalog snapshot.
+//!
+//! When the `embedded-catalog` feature is enabled, the snapshot is copied into `OUT_DIR`
+//! so it can be compiled into the binary, and its date is exposed through the
+//! `AWS_IAM_EXPANSION_SNAPSHOT_DATE` environment variable. The snapshot is read from the
+//! file pointed to by `AWS_IAM_EXPANSION_SNAPSHOT`, or from `catalog/aws_iam_actions.json`,
+//! and the build fails when neither exists. Builds that only need the feature to compile
+//! (e.g., CI) can point `AWS_IAM_EXPANSION_SNAPSHOT` at the small test fixture
+//! `catalog/fixture_aws_iam_actions.json`. The snapshot date is taken from
+//! `AWS_IAM_EXPANSION_SNAPSHOT_DATE` if set, or from the modification time of the snapshot
+//! file otherwise.
+
+const DEFAULT_SNAPSHOT_PATH: &str = "catalog/aws_iam_actions.json";
+
+include!("src/unix_date.rs");
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=AWS_IAM_EXPANSION_SNAPSHOT");
+    println!("cargo:rerun-if-env-changed=AWS_IAM_EXPANSION_SNAPSHOT_DATE");
+    if std::env::var_os("CARGO_FEATURE_EMBEDDED_CATALOG").is_none() {
+        return;
+    }
+
+    println!("cargo:rerun-if-changed={}", DEFAULT_SNAPSHOT_PATH);
+    let snapshot_path = match std::env::var("AWS_IAM_EXPANSION_SNAPSHOT") {
+        Ok(snapshot_path) => snapshot_path,
+        Err(_) if std::path::Path::new(DEFAULT_SNAPSHOT_PATH).is_file() => {
+            DEFAULT_SNAPSHOT_PATH.to_string()
+        }
+        Err(_) => panic!(
+            "The `embedded-catalog` feature requires a catalog snapshot, but there is none at '{}'. \
+             Copy a cached catalog (e.g., ~/.cache/aws_iam_expansion/aws_iam_actions.json) to '{}', \
+             or set AWS_IAM_EXPANSION_SNAPSHOT to its path.",
+            DEFAULT_SNAPSHOT_PATH, DEFAULT_SNAPSHOT_PATH
+        ),
+    };
+    println!("cargo:rerun-if-changed={}", snapshot_path);
+
+    let snapshot = std::fs::read_to_string(&snapshot_path).unwrap_or_else(|error| {
+        panic!(
+            "The `embedded-catalog` feature requires a catalog snapshot, but '{}' could not be read: {}",
+            snapshot_path, error
+        )
+    });
+    if let Err(error) = serde_json::from_str::<serde_json::Value>(&snapshot) {
+        panic!(
+            "The catalog snapshot '{}' is not valid JSON: {}",
+            snapshot_path, error
+        );
+    }
+
+    let snapshot_date = std::env::var("AWS_IAM_EXPANSION_SNAPSHOT_DATE").unwrap_or_else(|_| {
+        std::fs::metadata(&snapshot_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|elapsed| format_unix_date(elapsed.as_secs()))
+            .unwrap_or_else(|| "unknown".to_string())
+    });
+
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join("aws_iam_actions.json"), snapshot)
+        .expect("Could not write the catalog snapshot to OUT_DIR");
+    println!(
+        "cargo:rustc-env=AWS_IAM_EXPANSION_SNAPSHOT_DATE={}",
+        snapshot_date
+    );
+}
@@ -0,0 +1,596 @@
+//! The catalog of known AWS IAM services and actions.
+//!
+//! A [`Catalog`] owns the services loaded from a [`CatalogSource`](crate::source::CatalogSource),
+//! with services sharing a prefix merged together, and builds the indexes every command
+//! relies on once: the trie of case-folded action names used for pattern expansion, the
+//! case-insensitive lookup of actions by name, and the grouping of actions by access level.
+
+use std::collections::{BTreeMap, HashMap};
+
+/// An action listed with different access levels by services sharing a prefix.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ActionTypeConflict {
+    /// The name of the action, as first listed.
+    pub action: String,
+
+    /// The conflicting entries, in load order. The first one is kept in the catalog.
+    pub entries: Vec<ConflictingEntry>,
+}
+
+/// An entry of an [`ActionTypeConflict`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ConflictingEntry {
+    /// The full name of the service listing the action.
+    pub service_name: String,
+
+    /// The access level the service lists the action with.
+    pub access_level: String,
+}
+
+/// The details of an action, with the service it belongs to.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ActionDescription {
+    /// The service prefix (e.g., "iam").
+    pub service: String,
+
+    /// The full names of the services sharing the prefix
+    /// (e.g., "AWS Identity and Access Management (IAM)").
+    pub service_names: Vec<String>,
+
+    /// The action, with its access level and any metadata provided by the catalog source.
+    #[serde(flatten)]
+    pub action: crate::types::AwsAction,
+}
+
+/// The catalog of known AWS IAM services and actions, with its lookup indexes.
+pub struct Catalog {
+    /// The services of the catalog, keyed by lowercased service prefix.
+    services: BTreeMap<String, crate::types::AwsService>,
+
+    /// The full names of the services sharing each prefix, keyed by lowercased service
+    /// prefix, in load order.
+    service_names: HashMap<String, Vec<String>>,
+
+    /// The actions listed with different access levels by services sharing a prefix.
+    conflicts: Vec<ActionTypeConflict>,
+
+    /// The position of every action, keyed by case-folded name, as its service key and
+    /// its index in the actions of that service.
+    actions: HashMap<String, (String, usize)>,
+
+    /// The case-folded names of the actions of every access level, keyed by normalized
+    /// access level, in service order.
+    access_levels: HashMap<String, Vec<String>>,
+
+    /// The index used to expand action patterns.
+    index: crate::pattern::ActionIndex,
+}
+
+impl Catalog {
+    /// Builds a catalog from a list of services.
+    ///
+    /// Several AWS services can share a prefix. Services sharing a prefix (compared
+    /// case-insensitively) are merged into a single logical service, keeping the full names
+    /// of all of them: the actions of the others are appended to the first one, skipping the
+    /// actions already present (compared case-insensitively). When a duplicated action is
+    /// listed with different access levels, the first one is kept and the conflict is
+    /// recorded (see [`conflicts`](Self::conflicts)).
+    ///
+    /// # Arguments
+    ///
+    /// * `services` - The services to build the catalog from.
+    ///
+    /// # Returns
+    ///
+    /// A new `Catalog` owning the merged services and their indexes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::{catalog::Catalog, types::AwsService};
+    ///
+    /// let services = serde_json::from_value::<Vec<AwsService>>(serde_json::json!([
+    ///     {
+    ///         "service": "Amazon EC2",
+    ///         "servicePrefix": "ec2",
+    ///         "actions": [{ "action": "ec2:RunInstances", "type": "Write" }]
+    ///     },
+    ///     {
+    ///         "service": "AWS Systems Manager for EC2",
+    ///         "servicePrefix": "ec2",
+    ///         "actions": [
+    ///             { "action": "ec2:RunInstances", "type": "Write" },
+    ///             { "action": "ec2:DescribeInstances", "type": "List" }
+    ///         ]
+    ///     }
+    /// ]))
+    /// .unwrap();
+    ///
+    /// let catalog = Catalog::new(services);
+    /// assert_eq!(catalog.services().count(), 1);
+    /// assert_eq!(catalog.service_names("ec2"), ["Amazon EC2", "AWS Systems Manager for EC2"]);
+    /// assert_eq!(catalog.expand("ec2:*"), vec!["ec2:DescribeInstances", "ec2:RunInstances"]);
+    /// assert!(catalog.conflicts().is_empty());
+    /// ```
+    pub fn new<I>(services: I) -> Self
+    where
+        I: IntoIterator<Item = crate::types::AwsService>,
+    {
+        let mut merged: BTreeMap<String, crate::types::AwsService> = BTreeMap::new();
+        let mut service_names: HashMap<String, Vec<String>> = HashMap::new();
+        // The first service listing each action and its position in the merged service,
+        // keyed by service prefix and action name.
+        let mut known_actions: HashMap<(String, String), (String, usize)> = HashMap::new();
+        let mut conflicts: Vec<ActionTypeConflict> = Vec::new();
+        // The position of the conflict of every action, keyed by service prefix and action name.
+        let mut known_conflicts: HashMap<(String, String), usize> = HashMap::new();
+        for service in services {
+            let key = service.prefix.to_lowercase();
+            let names = service_names.entry(key.clone()).or_default();
+            if !names.contains(&service.name) {
+                names.push(service.name.clone());
+            }
+            let existing = merged
+                .entry(key.clone())
+                .or_insert_with(|| crate::types::AwsService {
+                    name: service.name.clone(),
+                    prefix: service.prefix.clone(),
+                    actions: Vec::new(),
+                });
+
+            for action in service.actions {
+                let folded = action.name.to_lowercase();
+                let action_key = (key.clone(), folded);
+                let Some((origin, position)) = known_actions.get(&action_key) else {
+                    known_actions
+                        .insert(action_key, (service.name.clone(), existing.actions.len()));
+                    existing.actions.push(action);
+                    continue;
+                };
+                let known = &existing.actions[*position];
+                if crate::expansion::normalize_access_level(&known.action_type)
+                    == crate::expansion::normalize_access_level(&action.action_type)
+                {
+                    continue;
+                }
+
+                let entry = ConflictingEntry {
+                    service_name: service.name.clone(),
+                    access_level: action.action_type.clone(),
+                };
+                match known_conflicts.get(&action_key) {
+                    Some(conflict) => conflicts[*conflict].entries.push(entry),
+                    None => {
+                        known_conflicts.insert(action_key, conflicts.len());
+                        conflicts.push(ActionTypeConflict {
+                            action: known.name.clone(),
+                            entries: vec![
+                                ConflictingEntry {
+                                    service_name: origin.clone(),
+                                    access_level: known.action_type.clone(),
+                                },
+                                entry,
+                            ],
+                        });
+                    }
+                }
+            }
+        }
+
+        let mut actions: HashMap<String, (String, usize)> = HashMap::new();
+        let mut access_levels: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, service) in &merged {
+            for (position, action) in service.actions.iter().enumerate() {
+                // An action name listed by services with different prefixes resolves to the
+                // first of them.
+                let folded = action.name.to_lowercase();
+                if let std::collections::hash_map::Entry::Vacant(entry) =
+                    actions.entry(folded.clone())
+                {
+                    entry.insert((key.clone(), position));
+                    access_levels
+                        .entry(crate::expansion::normalize_access_level(
+                            &action.action_type,
+                        ))
+                        .or_default()
+                        .push(folded);
+                }
+            }
+        }
+
+        let index = crate::pattern::ActionIndex::from_services(merged.values());
+
+        Catalog {
+            services: merged,
+            service_names,
+            conflicts,
+            actions,
+            access_levels,
+            index,
+        }
+    }
+
+    /// Loads a catalog from a catalog source.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source to load the services from.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the catalog, or an error if the source cannot be loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use aws_iam_expansion::{catalog::Catalog, source::AwsIamActionsSource};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let catalog = Catalog::from_source(&AwsIamActionsSource::default())?;
+    /// println!("{} actions", catalog.expand("*").len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_source(
+        source: &dyn crate::source::CatalogSource,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::new(source.load()?))
+    }
+
+    /// Returns the services of the catalog, sorted by service prefix.
+    pub fn services(&self) -> impl Iterator<Item = &crate::types::AwsService> {
+        self.services.values()
+    }
+
+    /// Returns the service with the given prefix, ignoring case.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The service prefix (e.g., "iam").
+    pub fn service(&self, prefix: &str) -> Option<&crate::types::AwsService> {
+        self.services.get(&prefix.to_lowercase())
+    }
+
+    /// Returns the full names of the services sharing a prefix, ignoring case, in load order.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The service prefix (e.g., "ec2").
+    pub fn service_names(&self, prefix: &str) -> &[String] {
+        self.service_names
+            .get(&prefix.to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns the actions listed with different access levels by services sharing a prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::{catalog::Catalog, types::AwsService};
+    ///
+    /// let catalog = Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([
+    ///     {
+    ///         "service": "Amazon S3",
+    ///         "servicePrefix": "s3",
+    ///         "actions": [{ "action": "s3:GetBucketTagging", "type": "Read" }]
+    ///     },
+    ///     {
+    ///         "service": "Amazon S3 on Outposts",
+    ///         "servicePrefix": "s3",
+    ///         "actions": [{ "action": "s3:GetBucketTagging", "type": "Tagging" }]
+    ///     }
+    /// ])).unwrap());
+    ///
+    /// let conflicts = catalog.conflicts();
+    /// assert_eq!(conflicts[0].action, "s3:GetBucketTagging");
+    /// assert_eq!(conflicts[0].entries[1].service_name, "Amazon S3 on Outposts");
+    /// assert_eq!(conflicts[0].entries[1].access_level, "Tagging");
+    /// assert_eq!(catalog.access_level("s3:GetBucketTagging"), Some("Read"));
+    /// ```
+    pub fn conflicts(&self) -> &[ActionTypeConflict] {
+        &self.conflicts
+    }
+
+    /// Searches services by prefix and full name.
+    ///
+    /// The query is matched case-insensitively against both the service prefix and the full
+    /// names of the services sharing it. Services whose prefix equals the query come first, then services
+    /// containing the query as a substring, then services matching it fuzzily, i.e., whose
+    /// prefix or name contains every non-blank character of the query in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The search query (e.g., "cognito sync", "s3").
+    ///
+    /// # Returns
+    ///
+    /// The matching services, by relevance and then by service prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::{catalog::Catalog, types::AwsService};
+    ///
+    /// let catalog = Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([
+    ///     { "service": "Amazon Cognito Sync", "servicePrefix": "cognito-sync", "actions": [] },
+    ///     { "service": "Amazon Cognito Identity", "servicePrefix": "cognito-identity", "actions": [] },
+    ///     { "service": "Amazon S3", "servicePrefix": "s3", "actions": [] }
+    /// ])).unwrap());
+    ///
+    /// let prefixes = |query: &str| {
+    ///     catalog.search(query).iter().map(|service| service.prefix.as_str()).collect::<Vec<&str>>()
+    /// };
+    /// assert_eq!(prefixes("cognito sync"), vec!["cognito-sync"]);
+    /// assert_eq!(prefixes("Cognito"), vec!["cognito-identity", "cognito-sync"]);
+    /// assert_eq!(prefixes("cogsync"), vec!["cognito-sync"]);
+    /// ```
+    pub fn search(&self, query: &str) -> Vec<&crate::types::AwsService> {
+        let query = query.trim().to_lowercase();
+        let mut matches = self
+            .services
+            .values()
+            .filter_map(|service| {
+                let prefix = service.prefix.to_lowercase();
+                let names = self
+                    .service_names(&prefix)
+                    .iter()
+                    .map(|name| name.to_lowercase())
+                    .collect::<Vec<String>>();
+                let rank = if prefix == query {
+                    0
+                } else if prefix.contains(&query) || names.iter().any(|name| name.contains(&query))
+                {
+                    1
+                } else if fuzzy_match(&query, &prefix)
+                    || names.iter().any(|name| fuzzy_match(&query, name))
+                {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, service))
+            })
+            .collect::<Vec<(usize, &crate::types::AwsService)>>();
+        matches.sort_by_key(|(rank, _)| *rank);
+        matches
+            .into_iter()
+            .map(|(_, service)| service)
+            .collect::<Vec<&crate::types::AwsService>>()
+    }
+
+    /// Returns the action with the given name, ignoring case.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The full action name (e.g., "s3:GetObject").
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::{catalog::Catalog, types::AwsService};
+    ///
+    /// let catalog = Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([{
+    ///     "service": "Amazon S3",
+    ///     "servicePrefix": "s3",
+    ///     "actions": [{ "action": "s3:GetObject", "type": "Read" }]
+    /// }])).unwrap());
+    ///
+    /// assert_eq!(catalog.action("S3:getobject").unwrap().action_type, "Read");
+    /// assert!(catalog.action("s3:PutObject").is_none());
+    /// ```
+    pub fn action(&self, name: &str) -> Option<&crate::types::AwsAction> {
+        let (service, position) = self.actions.get(&name.to_lowercase())?;
+        self.services[service].actions.get(*position)
+    }
+
+    /// Expands an IAM action pattern into every matching action, with canonical casing.
+    ///
+    /// See [`ActionIndex::expand`](crate::pattern::ActionIndex::expand).
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The action pattern (e.g., "s3:Get*").
+    pub fn expand(&self, pattern: &str) -> Vec<String> {
+        self.index.expand(pattern)
+    }
+
+    /// Returns the access level of an action, ignoring case.
+    ///
+    /// An action name listed by services with different prefixes resolves to the first of
+    /// them in prefix order, like [`action`](Self::action) and
+    /// [`by_access_level`](Self::by_access_level).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The full action name (e.g., "s3:GetObject").
+    pub fn access_level(&self, name: &str) -> Option<&str> {
+        self.action(name).map(|action| action.action_type.as_str())
+    }
+
+    /// Returns every action of an access level.
+    ///
+    /// Access levels are normalized like [`normalize_access_level`](crate::expansion::normalize_access_level),
+    /// so "permissions-management" matches "Permissions management".
+    ///
+    /// # Arguments
+    ///
+    /// * `access_level` - The access level (e.g., "Read", "permissions-management").
+    ///
+    /// # Returns
+    ///
+    /// The actions of the access level, sorted by service prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::{catalog::Catalog, types::AwsService};
+    ///
+    /// let catalog = Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([{
+    ///     "service": "AWS Identity and Access Management (IAM)",
+    ///     "servicePrefix": "iam",
+    ///     "actions": [
+    ///         { "action": "iam:GetRole", "type": "Read" },
+    ///         { "action": "iam:AttachRolePolicy", "type": "Permissions management" }
+    ///     ]
+    /// }])).unwrap());
+    ///
+    /// let actions = catalog.by_access_level("permissions-management");
+    /// assert_eq!(actions.len(), 1);
+    /// assert_eq!(actions[0].name, "iam:AttachRolePolicy");
+    /// ```
+    pub fn by_access_level(&self, access_level: &str) -> Vec<&crate::types::AwsAction> {
+        self.access_levels
+            .get(&crate::expansion::normalize_access_level(access_level))
+            .map(|actions| {
+                actions
+                    .iter()
+                    .filter_map(|action| self.action(action))
+                    .collect::<Vec<&crate::types::AwsAction>>()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Describes every action matching an IAM action pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The action name or pattern (e.g., "iam:PassRole", "iam:Pass*").
+    ///
+    /// # Returns
+    ///
+    /// The descriptions of the matching actions, in trie order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::{catalog::Catalog, types::AwsService};
+    ///
+    /// let catalog = Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([{
+    ///     "service": "AWS Identity and Access Management (IAM)",
+    ///     "servicePrefix": "iam",
+    ///     "actions": [{
+    ///         "action": "iam:PassRole",
+    ///         "type": "Write",
+    ///         "condition_keys": ["iam:PassedToService"]
+    ///     }]
+    /// }])).unwrap());
+    ///
+    /// let descriptions = catalog.describe("iam:passrole");
+    /// assert_eq!(descriptions[0].service_names, ["AWS Identity and Access Management (IAM)"]);
+    /// assert_eq!(descriptions[0].action.action_type, "Write");
+    /// assert_eq!(descriptions[0].action.condition_keys, vec!["iam:PassedToService"]);
+    /// assert!(catalog.describe("iam:Get*").is_empty());
+    /// ```
+    pub fn describe(&self, pattern: &str) -> Vec<ActionDescription> {
+        self.expand(pattern)
+            .iter()
+            .filter_map(|name| {
+                let (key, position) = self.actions.get(&name.to_lowercase())?;
+                let service = &self.services[key];
+                Some(ActionDescription {
+                    service: service.prefix.clone(),
+                    service_names: self.service_names(key).to_vec(),
+                    action: service.actions.get(*position)?.clone(),
+                })
+            })
+            .collect::<Vec<ActionDescription>>()
+    }
+
+    /// Returns the index used to expand action patterns.
+    pub fn index(&self) -> &crate::pattern::ActionIndex {
+        &self.index
+    }
+}
+
+/// Returns whether every non-blank character of a query appears in a candidate, in order.
+///
+/// Both the query and the candidate are expected to be lowercased. Spaces, `-` and `_` are
+/// ignored in the query, so that "cognito sync" matches "cognito-sync".
+fn fuzzy_match(query: &str, candidate: &str) -> bool {
+    let mut candidate = candidate.chars();
+    query
+        .chars()
+        .filter(|ch| !ch.is_whitespace() && *ch != '-' && *ch != '_')
+        .all(|ch| candidate.any(|candidate_ch| candidate_ch == ch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::services;
+
+    #[test]
+    fn merges_overlapping_sources_sharing_a_prefix() {
+        let catalog = Catalog::new(services(serde_json::json!([
+            {
+                "service": "Amazon S3",
+                "servicePrefix": "s3",
+                "actions": [
+                    { "action": "s3:GetObject", "type": "Read" },
+                    { "action": "s3:GetBucketTagging", "type": "Read" }
+                ]
+            },
+            {
+                "service": "Amazon S3 on Outposts",
+                "servicePrefix": "S3",
+                "actions": [
+                    { "action": "s3:GetBucketTagging", "type": "Tagging" },
+                    { "action": "s3:PutObject", "type": "Write" }
+                ]
+            }
+        ])));
+
+        assert_eq!(
+            catalog.expand("s3:*"),
+            vec!["s3:GetBucketTagging", "s3:GetObject", "s3:PutObject"]
+        );
+        assert_eq!(catalog.conflicts().len(), 1);
+        assert_eq!(catalog.conflicts()[0].action, "s3:GetBucketTagging");
+        assert_eq!(catalog.access_level("s3:GetBucketTagging"), Some("Read"));
+    }
+
+    #[test]
+    fn keeps_actions_listed_under_several_prefixes_apart() {
+        // The same action name listed under another, shorter service must neither be
+        // compared with the wrong service nor index past its actions.
+        let catalog = Catalog::new(services(serde_json::json!([
+            {
+                "service": "Amazon S3",
+                "servicePrefix": "s3",
+                "actions": [
+                    { "action": "s3:GetObject", "type": "Read" },
+                    { "action": "s3:PutObject", "type": "Write" },
+                    { "action": "s3:ListBucket", "type": "List" }
+                ]
+            },
+            {
+                "service": "Amazon S3 Object Lambda",
+                "servicePrefix": "s3-object-lambda",
+                "actions": [{ "action": "s3:ListBucket", "type": "Read" }]
+            }
+        ])));
+
+        assert_eq!(
+            catalog
+                .services()
+                .map(|service| service.actions.len())
+                .collect::<Vec<usize>>(),
+            vec![3, 1]
+        );
+        assert!(catalog.conflicts().is_empty());
+
+        // The action resolves to the same entry whichever way it is looked up.
+        assert_eq!(catalog.access_level("s3:ListBucket"), Some("List"));
+        assert_eq!(catalog.index().access_level("s3:ListBucket"), Some("List"));
+        let names = |access_level: &str| {
+            catalog
+                .by_access_level(access_level)
+                .iter()
+                .map(|action| action.name.as_str())
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(names("List"), vec!["s3:ListBucket"]);
+        assert_eq!(names("Read"), vec!["s3:GetObject"]);
+    }
+}
@@ -0,0 +1,714 @@
+//! Extraction of the policy documents embedded in CloudFormation templates.
+//!
+//! Most IAM policies are not standalone files but properties of CloudFormation resources:
+//! the `PolicyDocument` of `AWS::IAM::Policy` and `AWS::IAM::ManagedPolicy` resources, the
+//! inline `Policies` and the `AssumeRolePolicyDocument` of roles, and the resource policies
+//! of buckets, queues, topics, keys and the like. This module finds these documents in JSON
+//! and YAML templates, and resolves the intrinsic functions (`Ref`, `Fn::Sub`, `Fn::Join`,
+//! `Fn::If`, ...) they contain into plain strings, so that they can be expanded and analyzed
+//! like any other policy.
+
+use crate::policy::PolicyFormat;
+use serde_json::Value;
+
+/// The properties of CloudFormation resource types holding a policy document.
+const POLICY_PROPERTIES: &[(&str, &str)] = &[
+    ("AWS::IAM::Policy", "PolicyDocument"),
+    ("AWS::IAM::ManagedPolicy", "PolicyDocument"),
+    ("AWS::IAM::RolePolicy", "PolicyDocument"),
+    ("AWS::IAM::UserPolicy", "PolicyDocument"),
+    ("AWS::IAM::GroupPolicy", "PolicyDocument"),
+    ("AWS::IAM::Role", "AssumeRolePolicyDocument"),
+    ("AWS::S3::BucketPolicy", "PolicyDocument"),
+    ("AWS::S3::AccessPoint", "Policy"),
+    ("AWS::SQS::QueuePolicy", "PolicyDocument"),
+    ("AWS::SNS::TopicPolicy", "PolicyDocument"),
+    ("AWS::KMS::Key", "KeyPolicy"),
+    ("AWS::SecretsManager::ResourcePolicy", "ResourcePolicy"),
+    ("AWS::ECR::Repository", "RepositoryPolicyText"),
+    ("AWS::EFS::FileSystem", "FileSystemPolicy"),
+    ("AWS::Backup::BackupVault", "AccessPolicy"),
+    ("AWS::CodeArtifact::Repository", "PermissionsPolicyDocument"),
+    ("AWS::OpenSearchService::Domain", "AccessPolicies"),
+    ("AWS::Elasticsearch::Domain", "AccessPolicies"),
+];
+
+/// The CloudFormation resource types with a `Policies` property listing inline policies.
+const INLINE_POLICY_TYPES: &[&str] = &["AWS::IAM::Role", "AWS::IAM::User", "AWS::IAM::Group"];
+
+/// A policy document embedded in a CloudFormation template.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TemplatePolicy {
+    /// The logical ID of the resource holding the policy (e.g., "AppRole").
+    pub logical_id: String,
+
+    /// The type of the resource holding the policy (e.g., "AWS::IAM::Role").
+    pub resource_type: String,
+
+    /// The property holding the policy (e.g., "Policies[0].PolicyDocument").
+    pub property: String,
+
+    /// The name of the inline policy, for the `Policies` of roles, users and groups.
+    pub policy_name: Option<String>,
+
+    /// The policy document, with its intrinsic functions resolved.
+    pub document: crate::types::AWSPolicyDocument,
+}
+
+impl std::fmt::Display for TemplatePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}) {}",
+            self.logical_id, self.resource_type, self.property
+        )?;
+        if let Some(policy_name) = &self.policy_name {
+            write!(f, " ({})", policy_name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts a YAML value to JSON, rewriting the short form of intrinsic functions
+/// (e.g., `!Sub`, `!GetAtt Role.Arn`) to their full form (`Fn::Sub`, `Fn::GetAtt`).
+fn yaml_to_json(value: serde_norway::Value) -> Result<Value, Box<dyn std::error::Error>> {
+    Ok(match value {
+        serde_norway::Value::Null => Value::Null,
+        serde_norway::Value::Bool(boolean) => Value::Bool(boolean),
+        serde_norway::Value::Number(number) => serde_json::to_value(number)?,
+        serde_norway::Value::String(string) => Value::String(string),
+        serde_norway::Value::Sequence(values) => Value::Array(
+            values
+                .into_iter()
+                .map(yaml_to_json)
+                .collect::<Result<Vec<Value>, _>>()?,
+        ),
+        serde_norway::Value::Mapping(mapping) => {
+            let mut object = serde_json::Map::new();
+            for (key, value) in mapping {
+                let key = match key {
+                    serde_norway::Value::String(key) => key,
+                    key => serde_norway::to_string(&key)?.trim_end().to_string(),
+                };
+                object.insert(key, yaml_to_json(value)?);
+            }
+            Value::Object(object)
+        }
+        serde_norway::Value::Tagged(tagged) => {
+            let function = tagged.tag.to_string();
+            let function = function.trim_start_matches('!');
+            let argument = yaml_to_json(tagged.value)?;
+            let (name, argument) = match (function, argument) {
+                ("Ref" | "Condition", argument) => (function.to_string(), argument),
+                ("GetAtt", Value::String(attribute)) => (
+                    "Fn::GetAtt".to_string(),
+                    match attribute.split_once('.') {
+                        Some((resource, attribute)) => serde_json::json!([resource, attribute]),
+                        None => Value::String(attribute),
+                    },
+                ),
+                (function, argument) => (format!("Fn::{}", function), argument),
+            };
+            serde_json::json!({ name: argument })
+        }
+    })
+}
+
+/// Parses a CloudFormation template.
+///
+/// # Arguments
+///
+/// * `source` - The source text of the template.
+/// * `format` - The format of the template. YAML templates may use the short form of
+///   intrinsic functions (e.g., `!Ref`), which is rewritten to the full form (`Ref`).
+///
+/// # Returns
+///
+/// A `Result` containing the template as a JSON value, or an error if it cannot be parsed.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::{cloudformation::parse_template, policy::PolicyFormat};
+///
+/// let template = parse_template(
+///     "Resources:\n  Topic:\n    Type: AWS::SNS::Topic\n    Properties:\n      TopicName: !Sub '${AWS::StackName}-events'\n",
+///     PolicyFormat::Yaml,
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     template["Resources"]["Topic"]["Properties"]["TopicName"],
+///     serde_json::json!({ "Fn::Sub": "${AWS::StackName}-events" })
+/// );
+/// ```
+pub fn parse_template(
+    source: &str,
+    format: PolicyFormat,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    match format {
+        PolicyFormat::Json => Ok(serde_json::from_str::<Value>(source)?),
+        PolicyFormat::Yaml => yaml_to_json(serde_norway::from_str::<serde_norway::Value>(source)?),
+    }
+}
+
+/// Returns the name of the intrinsic function a template value calls, with its argument.
+fn intrinsic_function(value: &Value) -> Option<(&str, &Value)> {
+    let Value::Object(object) = value else {
+        return None;
+    };
+    if object.len() != 1 {
+        return None;
+    }
+    let (name, argument) = object.iter().next()?;
+    (name == "Ref" || name.starts_with("Fn::")).then_some((name.as_str(), argument))
+}
+
+/// Renders a resolved template value as a string, for the functions building strings.
+fn render_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Number(number) => number.to_string(),
+        Value::Bool(boolean) => boolean.to_string(),
+        _ => "${Unresolved}".to_string(),
+    }
+}
+
+/// The list-valued keys of policy documents, statements and principals, into which the
+/// values of the branches of a `Fn::If` are spliced.
+const LIST_KEYS: &[&str] = &[
+    "Statement",
+    "Action",
+    "NotAction",
+    "Resource",
+    "NotResource",
+    "AWS",
+    "Service",
+    "Federated",
+    "CanonicalUser",
+];
+
+/// Resolves the intrinsic functions of a template value.
+///
+/// References and attributes that can only be known once the stack is deployed are
+/// rendered as `${Name}` placeholders (e.g., `Ref: BucketName` as `${BucketName}`), like
+/// `Fn::Sub` does. Both branches of a `Fn::If` are kept, so that the resolved policy covers
+/// every action and resource the template may grant, and `Ref: AWS::NoValue` removes the
+/// value.
+///
+/// The branches of a `Fn::If` under a list-valued key (see [`LIST_KEYS`]) are spliced into
+/// a single list. Under any other key (e.g., `Effect`, `Sid` or `Condition`), the enclosing
+/// object is forked instead, once per branch, so that a statement with a conditional
+/// `Effect` becomes one statement per effect.
+///
+/// # Returns
+///
+/// The values the template value may take: none for `AWS::NoValue`, several for the
+/// branches of a `Fn::If` and the forks of an object, and one otherwise.
+fn resolve(value: &Value) -> Vec<Value> {
+    if let Some((name, argument)) = intrinsic_function(value) {
+        return resolve_function(name, argument);
+    }
+    match value {
+        Value::Array(values) => vec![Value::Array(resolve_array(values))],
+        Value::Object(object) => {
+            let mut forks = vec![serde_json::Map::new()];
+            for (key, value) in object {
+                if LIST_KEYS.contains(&key.as_str()) {
+                    let mut values = resolve(value);
+                    let value = match values.len() {
+                        0 => continue,
+                        1 => values.remove(0),
+                        _ => Value::Array(splice(values)),
+                    };
+                    for fork in &mut forks {
+                        fork.insert(key.clone(), value.clone());
+                    }
+                    continue;
+                }
+
+                let branches = resolve_branches(value);
+                let mut forked: Vec<serde_json::Map<String, Value>> = Vec::new();
+                for fork in &forks {
+                    for branch in &branches {
+                        let mut fork = fork.clone();
+                        if let Some(value) = branch {
+                            fork.insert(key.clone(), value.clone());
+                        }
+                        if !forked.contains(&fork) {
+                            forked.push(fork);
+                        }
+                    }
+                }
+                forks = forked;
+            }
+            forks.into_iter().map(Value::Object).collect()
+        }
+        value => vec![value.clone()],
+    }
+}
+
+/// Resolves a template value into the values a key may take, `None` standing for a branch
+/// of a `Fn::If` (or a `Ref: AWS::NoValue`) that removes the key.
+fn resolve_branches(value: &Value) -> Vec<Option<Value>> {
+    let branches = match intrinsic_function(value) {
+        Some(("Fn::If", Value::Array(arguments))) if arguments.len() == 3 => {
+            vec![&arguments[1], &arguments[2]]
+        }
+        _ => vec![value],
+    };
+
+    let mut resolved: Vec<Option<Value>> = Vec::new();
+    for branch in branches {
+        let values = resolve(branch);
+        let values = if values.is_empty() {
+            vec![None]
+        } else {
+            values.into_iter().map(Some).collect()
+        };
+        for value in values {
+            if !resolved.contains(&value) {
+                resolved.push(value);
+            }
+        }
+    }
+    resolved
+}
+
+/// Resolves the intrinsic functions of the values of an array, splicing the values of the
+/// functions resolving to several values or to an array.
+fn resolve_array(values: &[Value]) -> Vec<Value> {
+    let mut resolved: Vec<Value> = Vec::new();
+    for value in values {
+        let values = if intrinsic_function(value).is_some() {
+            splice(resolve(value))
+        } else {
+            resolve(value)
+        };
+        for value in values {
+            if !resolved.contains(&value) {
+                resolved.push(value);
+            }
+        }
+    }
+    resolved
+}
+
+/// Merges the values a function resolves to into a single list, splicing the arrays (e.g.,
+/// the list branches of a `Fn::If`) and dropping duplicates.
+fn splice(values: Vec<Value>) -> Vec<Value> {
+    let mut spliced: Vec<Value> = Vec::new();
+    for value in values {
+        let values = match value {
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            if !spliced.contains(&value) {
+                spliced.push(value);
+            }
+        }
+    }
+    spliced
+}
+
+/// Resolves an intrinsic function call (see [`resolve`]).
+fn resolve_function(name: &str, argument: &Value) -> Vec<Value> {
+    let first = |value: &Value| resolve(value).into_iter().next().unwrap_or(Value::Null);
+    let string = |value: String| vec![Value::String(value)];
+
+    match (name, argument) {
+        ("Ref", Value::String(reference)) if reference == "AWS::NoValue" => Vec::new(),
+        ("Ref", Value::String(reference)) => string(format!("${{{}}}", reference)),
+        ("Fn::GetAtt", Value::Array(attribute)) => string(format!(
+            "${{{}}}",
+            attribute
+                .iter()
+                .map(|part| render_string(&first(part)))
+                .collect::<Vec<String>>()
+                .join(".")
+        )),
+        ("Fn::GetAtt", Value::String(attribute)) => string(format!("${{{}}}", attribute)),
+        ("Fn::Sub", Value::String(template)) => string(template.clone()),
+        ("Fn::Sub", Value::Array(arguments)) => {
+            let mut template = arguments
+                .first()
+                .map(|template| render_string(&first(template)))
+                .unwrap_or_default();
+            if let Some(Value::Object(variables)) = arguments.get(1) {
+                for (variable, value) in variables {
+                    template = template
+                        .replace(&format!("${{{}}}", variable), &render_string(&first(value)));
+                }
+            }
+            string(template)
+        }
+        ("Fn::Join", Value::Array(arguments)) if arguments.len() == 2 => {
+            let delimiter = render_string(&first(&arguments[0]));
+            match first(&arguments[1]) {
+                Value::Array(parts) => string(
+                    parts
+                        .iter()
+                        .map(render_string)
+                        .collect::<Vec<String>>()
+                        .join(&delimiter),
+                ),
+                parts => string(render_string(&parts)),
+            }
+        }
+        ("Fn::Select", Value::Array(arguments)) if arguments.len() == 2 => {
+            let index = render_string(&first(&arguments[0])).parse::<usize>().ok();
+            match (index, first(&arguments[1])) {
+                (Some(index), Value::Array(values)) if index < values.len() => {
+                    vec![values[index].clone()]
+                }
+                _ => string(format!("${{{}}}", name)),
+            }
+        }
+        ("Fn::If", Value::Array(arguments)) if arguments.len() == 3 => {
+            let mut values: Vec<Value> = Vec::new();
+            for value in resolve(&arguments[1])
+                .into_iter()
+                .chain(resolve(&arguments[2]))
+            {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+            values
+        }
+        (name, _) => string(format!("${{{}}}", name)),
+    }
+}
+
+/// Parses a policy document property, resolving its intrinsic functions.
+///
+/// Policy documents may be given as JSON objects or, for some resource types, as JSON
+/// strings.
+///
+/// # Returns
+///
+/// The documents the property may hold: none for `AWS::NoValue`, one per branch of a
+/// `Fn::If` wrapping the whole document, and one otherwise.
+fn template_policy_documents(
+    value: &Value,
+) -> Result<Vec<crate::types::AWSPolicyDocument>, crate::policy::PolicyDiagnostic> {
+    resolve(value)
+        .into_iter()
+        .map(|document| match document {
+            Value::String(policy_json) => crate::policy::parse_policy(&policy_json),
+            document => crate::policy::parse_policy_value(&document),
+        })
+        .collect()
+}
+
+/// Finds the policy documents embedded in a CloudFormation template.
+///
+/// The policy properties of the IAM resource types (`AWS::IAM::Policy`, `ManagedPolicy`,
+/// `RolePolicy`, `UserPolicy` and `GroupPolicy`, the `AssumeRolePolicyDocument` of roles
+/// and the inline `Policies` of roles, users and groups) and of the resource policy types
+/// (e.g., `AWS::S3::BucketPolicy`, `AWS::SQS::QueuePolicy`, `AWS::KMS::Key`) are extracted,
+/// with their intrinsic functions resolved: references and attributes become `${Name}`
+/// placeholders, `Fn::Sub` and `Fn::Join` are evaluated as far as possible, and both
+/// branches of a `Fn::If` are kept. The branches of a `Fn::If` in a list-valued element
+/// (e.g., `Action` or `Resource`) are spliced into a single list, a `Fn::If` in any other
+/// element (e.g., `Effect`) yields one statement per branch, and a `Fn::If` wrapping a
+/// whole policy document yields one policy per branch, labelled with the branch (e.g.,
+/// "PolicyDocument (Fn::If branch 1)").
+///
+/// # Arguments
+///
+/// * `template` - The parsed template (see [`parse_template`]).
+///
+/// # Returns
+///
+/// A `Result` containing the policies, in template order.
+///
+/// # Errors
+///
+/// Returns an error naming the resource and property of the first policy document that
+/// cannot be parsed.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::{
+///     cloudformation::{parse_template, template_policies},
+///     policy::PolicyFormat,
+/// };
+///
+/// let template = parse_template(
+///     r#"
+/// Resources:
+///   AppRole:
+///     Type: AWS::IAM::Role
+///     Properties:
+///       AssumeRolePolicyDocument:
+///         Statement:
+///           - Effect: Allow
+///             Principal: { Service: lambda.amazonaws.com }
+///             Action: sts:AssumeRole
+///       Policies:
+///         - PolicyName: Artifacts
+///           PolicyDocument:
+///             Statement:
+///               - Effect: Allow
+///                 Action: !If [IsProd, s3:GetObject, s3:*]
+///                 Resource:
+///                   - !Sub arn:aws:s3:::${Bucket}/*
+///                   - !Join ['', ['arn:aws:s3:::', !Ref Bucket]]
+/// "#,
+///     PolicyFormat::Yaml,
+/// )
+/// .unwrap();
+///
+/// let policies = template_policies(&template).unwrap();
+/// assert_eq!(policies.len(), 2);
+/// assert_eq!(policies[1].to_string(), "AppRole (AWS::IAM::Role) Policies[0].PolicyDocument (Artifacts)");
+/// let statement = policies[1].document.statement.iter().next().unwrap();
+/// assert_eq!(statement.action.as_ref().unwrap().len(), 2);
+/// assert_eq!(
+///     statement.resource.as_ref().unwrap().iter().collect::<Vec<&String>>(),
+///     vec!["arn:aws:s3:::${Bucket}/*", "arn:aws:s3:::${Bucket}"]
+/// );
+/// ```
+pub fn template_policies(
+    template: &Value,
+) -> Result<Vec<TemplatePolicy>, Box<dyn std::error::Error>> {
+    let mut policies: Vec<TemplatePolicy> = Vec::new();
+    let Some(Value::Object(resources)) = template.get("Resources") else {
+        return Ok(policies);
+    };
+
+    for (logical_id, resource) in resources {
+        let Some(resource_type) = resource.get("Type").and_then(Value::as_str) else {
+            continue;
+        };
+        let Some(properties) = resource.get("Properties") else {
+            continue;
+        };
+        let mut add_policy = |property: String, policy_name: Option<String>, value: &Value| {
+            let documents = template_policy_documents(value).map_err(|error| {
+                format!(
+                    "Could not parse the policy document of {} ({}) {}: {}",
+                    logical_id, resource_type, property, error
+                )
+            })?;
+            let branches = documents.len();
+            for (branch, document) in documents.into_iter().enumerate() {
+                policies.push(TemplatePolicy {
+                    logical_id: logical_id.clone(),
+                    resource_type: resource_type.to_string(),
+                    property: if branches > 1 {
+                        format!("{} (Fn::If branch {})", property, branch)
+                    } else {
+                        property.clone()
+                    },
+                    policy_name: policy_name.clone(),
+                    document,
+                });
+            }
+            Ok::<(), String>(())
+        };
+
+        for (policy_type, property) in POLICY_PROPERTIES {
+            if *policy_type == resource_type
+                && let Some(value) = properties.get(*property)
+            {
+                add_policy(property.to_string(), None, value)?;
+            }
+        }
+
+        if INLINE_POLICY_TYPES.contains(&resource_type)
+            && let Some(inline_policies) = properties.get("Policies")
+        {
+            // The inline policies of every branch of a `Fn::If` are kept, each once.
+            let mut seen: Vec<Value> = Vec::new();
+            let branches = resolve(inline_policies);
+            for (index, inline_policy) in branches
+                .iter()
+                .filter_map(Value::as_array)
+                .flat_map(|inline_policies| inline_policies.iter().enumerate())
+            {
+                if seen.contains(inline_policy) {
+                    continue;
+                }
+                seen.push(inline_policy.clone());
+                let Some(document) = inline_policy.get("PolicyDocument") else {
+                    continue;
+                };
+                add_policy(
+                    format!("Policies[{}].PolicyDocument", index),
+                    inline_policy.get("PolicyName").map(render_string),
+                    document,
+                )?;
+            }
+        }
+    }
+    Ok(policies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Finds the policies of a YAML template.
+    fn policies(template: &str) -> Vec<TemplatePolicy> {
+        template_policies(&parse_template(template, PolicyFormat::Yaml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn splices_list_branches_of_mapping_values() {
+        let policies = policies(
+            r#"
+Resources:
+  Policy:
+    Type: AWS::IAM::ManagedPolicy
+    Properties:
+      PolicyDocument:
+        Statement:
+          - Effect: Allow
+            Action: !If [IsProd, [s3:GetObject], [s3:GetObject, s3:PutObject]]
+            Resource: "*"
+"#,
+        );
+        let statement = policies[0].document.statement.iter().next().unwrap();
+        assert_eq!(
+            statement
+                .action
+                .as_ref()
+                .unwrap()
+                .iter()
+                .collect::<Vec<&String>>(),
+            vec!["s3:GetObject", "s3:PutObject"]
+        );
+    }
+
+    #[test]
+    fn splices_list_and_scalar_branches() {
+        let policies = policies(
+            r#"
+Resources:
+  Policy:
+    Type: AWS::IAM::ManagedPolicy
+    Properties:
+      PolicyDocument:
+        Statement:
+          - Effect: Allow
+            Action: [iam:GetRole, !If [IsProd, iam:PassRole, [iam:PassRole, iam:CreateRole]]]
+            Resource: "*"
+"#,
+        );
+        let statement = policies[0].document.statement.iter().next().unwrap();
+        assert_eq!(
+            statement
+                .action
+                .as_ref()
+                .unwrap()
+                .iter()
+                .collect::<Vec<&String>>(),
+            vec!["iam:GetRole", "iam:PassRole", "iam:CreateRole"]
+        );
+    }
+
+    #[test]
+    fn forks_statements_with_conditional_scalars() {
+        let policies = policies(
+            r#"
+Resources:
+  Policy:
+    Type: AWS::IAM::ManagedPolicy
+    Properties:
+      PolicyDocument:
+        Statement:
+          - Sid: PutObject
+            Effect: !If [IsProd, Deny, Allow]
+            Action: s3:PutObject
+            Resource: "*"
+            Condition: !If [IsProd, { Bool: { aws:SecureTransport: "false" } }, !Ref AWS::NoValue]
+          - Effect: Allow
+            Action: s3:GetObject
+            Resource: "*"
+"#,
+        );
+        let statements = policies[0]
+            .document
+            .statement
+            .iter()
+            .map(|statement| {
+                (
+                    statement.sid.as_deref().unwrap_or_default(),
+                    statement.effect.as_str(),
+                    statement.condition.is_some(),
+                )
+            })
+            .collect::<Vec<(&str, &str, bool)>>();
+        assert_eq!(
+            statements,
+            vec![
+                ("PutObject", "Deny", true),
+                ("PutObject", "Deny", false),
+                ("PutObject", "Allow", true),
+                ("PutObject", "Allow", false),
+                ("", "Allow", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_every_branch_of_a_conditional_policy_document() {
+        let policies = policies(
+            r#"
+Resources:
+  Bucket:
+    Type: AWS::S3::BucketPolicy
+    Properties:
+      PolicyDocument: !If
+        - IsProd
+        - Statement: [{ Effect: Allow, Action: s3:GetObject, Resource: "*" }]
+        - Statement: [{ Effect: Allow, Action: s3:*, Resource: "*" }]
+"#,
+        );
+        assert_eq!(policies.len(), 2);
+        assert_eq!(
+            policies[1].to_string(),
+            "Bucket (AWS::S3::BucketPolicy) PolicyDocument (Fn::If branch 1)"
+        );
+        let statement = policies[1].document.statement.iter().next().unwrap();
+        assert_eq!(
+            statement
+                .action
+                .as_ref()
+                .unwrap()
+                .iter()
+                .collect::<Vec<&String>>(),
+            vec!["s3:*"]
+        );
+    }
+
+    #[test]
+    fn keeps_the_inline_policies_of_every_branch() {
+        let policies = policies(
+            r#"
+Resources:
+  AppRole:
+    Type: AWS::IAM::Role
+    Properties:
+      Policies: !If
+        - IsProd
+        - - PolicyName: Read
+            PolicyDocument: { Statement: [{ Effect: Allow, Action: s3:GetObject, Resource: "*" }] }
+        - - PolicyName: Read
+            PolicyDocument: { Statement: [{ Effect: Allow, Action: s3:GetObject, Resource: "*" }] }
+          - PolicyName: Write
+            PolicyDocument: { Statement: [{ Effect: Allow, Action: s3:PutObject, Resource: "*" }] }
+"#,
+        );
+        assert_eq!(
+            policies
+                .iter()
+                .map(|policy| policy.policy_name.as_deref().unwrap())
+                .collect::<Vec<&str>>(),
+            vec!["Read", "Write"]
+        );
+    }
+}
@@ -1,11 +1,12 @@
 //! Command-line interface and argument parsing for the AWS IAM expansion toolkit.
 //!
 //! This module defines the CLI structure and handles subcommand execution for expanding
-//! AWS IAM permissions. It provides functionality to list available AWS services and
-//! expand actions for a specific service using prefix matching.
+//! AWS IAM permissions. It provides functionality to list available AWS services, expand
+//! actions for a specific service or the wildcards of policy files, templates and plans,
+//! and manage the local cache.
 
 use clap::Subcommand;
-use std::collections::HashMap;
+use std::collections::HashSet;
 
 /// Represents the "expand" subcommand for expanding AWS IAM actions.
 ///
@@ -16,7 +17,9 @@ use std::collections::HashMap;
 pub struct ExpandSubCommand {
     /// The action name prefix to filter actions (e.g., "Create" to find "CreateUser", "CreateRole").
     ///
-    /// Wildcards (*) in the prefix are automatically removed during matching.
+    /// A prefix without wildcards matches every action starting with it. A prefix containing
+    /// IAM wildcards (`*` or `?`) is matched as a pattern against the whole action name
+    /// (e.g., "*Tagging" or "Describe*Attribute").
     /// If not provided, all actions for the service are returned.
     #[arg(long, required = false, requires = "service_name")]
     prefix: Option<String>,
@@ -26,19 +29,39 @@ pub struct ExpandSubCommand {
     /// This is the service prefix that appears in IAM action names (e.g., "iam" in "iam:CreateUser").
     #[arg(long, required = true)]
     service_name: String,
+
+    /// Only list actions of this access level (e.g., "read", "list", "write", "tagging",
+    /// "permissions-management"). Can be repeated.
+    #[arg(long = "access-level")]
+    access_levels: Vec<String>,
+
+    /// Do not list actions of this access level. Can be repeated.
+    #[arg(long = "exclude-access-level")]
+    exclude_access_levels: Vec<String>,
+
+    /// Print the access level next to each action.
+    #[arg(long = "show-access-level")]
+    show_access_level: bool,
+
+    /// Print the resource types (with their ARN formats), condition keys and dependent
+    /// actions of each action.
+    ///
+    /// This metadata is only available when the catalog is loaded with `--service-reference`.
+    #[arg(long = "show-metadata")]
+    show_metadata: bool,
 }
 
 impl ExpandSubCommand {
     /// Executes the expand subcommand to find and display all actions for a given service.
     ///
-    /// This function builds a trie data structure from all available IAM actions for efficient
-    /// prefix searching. It then queries the trie with the service name and optional action prefix
-    /// to retrieve matching actions and displays them to the user.
+    /// This function uses the catalog index of all available IAM actions for efficient,
+    /// case-insensitive prefix searching. It expands the pattern built from the service name and optional
+    /// action prefix to retrieve matching actions and displays them to the user, optionally
+    /// filtered by access level.
     ///
     /// # Arguments
     ///
-    /// * `available_services_permissions` - A HashMap mapping service prefixes to their associated
-    ///   service and action data, typically populated from the AWS IAM actions JSON.
+    /// * `catalog` - The catalog of known AWS IAM services and actions.
     ///
     /// # Returns
     ///
@@ -49,43 +72,162 @@ impl ExpandSubCommand {
     ///
     /// This function will exit the program with exit code 1 if the specified service is not found
     /// in the available services.
-    fn handle(
-        &self,
-        available_services_permissions: HashMap<String, Vec<crate::types::AwsService>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
         println!(
             "[+] Expanding AWS IAM actions for '{}' service...",
             self.service_name
         );
 
-        let services_permissions: Vec<String> = available_services_permissions
-            .values()
-            .flatten()
-            .flat_map(|service| service.actions.iter().map(|action| action.name.to_string()))
-            .collect::<Vec<String>>();
-
-        let trie = trie_rs::Trie::from_iter(services_permissions);
-        let service_name = self.service_name.as_str();
+        let service_name = self.service_name.to_lowercase();
+        let service_name = service_name.as_str();
 
-        if !available_services_permissions.contains_key(service_name) {
+        if catalog.service(service_name).is_none() {
             eprintln!("[!] Error: Service '{}' not found.", service_name);
             std::process::exit(1);
         }
-        let trie_query = match &self.prefix {
-            Some(prefix) => format!("{}:{}", service_name, prefix.replace('*', "")),
-            None => format!("{}:", service_name),
+        let pattern = match &self.prefix {
+            Some(prefix) if crate::pattern::has_wildcard(prefix) => {
+                format!("{}:{}", service_name, prefix)
+            }
+            Some(prefix) => format!("{}:{}*", service_name, prefix),
+            None => format!("{}:*", service_name),
         };
-        trie.predictive_search(trie_query)
-            .collect::<Vec<String>>()
+        let access_level_filter = crate::expansion::AccessLevelFilter::new(
+            &self.access_levels,
+            &self.exclude_access_levels,
+        );
+        catalog
+            .expand(&pattern)
             .into_iter()
+            .filter(|action| access_level_filter.allows(catalog.access_level(action)))
             .for_each(|action| {
-                println!("\t[-] {}", action);
+                if self.show_access_level {
+                    println!(
+                        "\t[-] {} ({})",
+                        action,
+                        catalog.access_level(&action).unwrap_or("Unknown")
+                    );
+                } else {
+                    println!("\t[-] {}", action);
+                }
+                if self.show_metadata
+                    && let Some(aws_action) = catalog.action(&action)
+                {
+                    print_action_metadata(aws_action, "\t\t");
+                }
             });
 
         Ok(())
     }
 }
 
+/// Prints the resource types, condition keys and dependent actions of an action.
+///
+/// # Arguments
+///
+/// * `action` - The action whose metadata to print.
+/// * `indent` - The indentation prepended to every line.
+fn print_action_metadata(action: &crate::types::AwsAction, indent: &str) {
+    if action.resource_types.is_empty()
+        && action.condition_keys.is_empty()
+        && action.dependent_actions.is_empty()
+    {
+        println!("{}[*] No metadata available", indent);
+        return;
+    }
+
+    for resource_type in &action.resource_types {
+        let required = if resource_type.required == Some(true) {
+            " (required)"
+        } else {
+            ""
+        };
+        if resource_type.arn_formats.is_empty() {
+            println!(
+                "{}[*] Resource type: {}{}",
+                indent, resource_type.name, required
+            );
+        } else {
+            println!(
+                "{}[*] Resource type: {}{}: {}",
+                indent,
+                resource_type.name,
+                required,
+                resource_type.arn_formats.join(", ")
+            );
+        }
+    }
+    if !action.condition_keys.is_empty() {
+        println!(
+            "{}[*] Condition keys: {}",
+            indent,
+            action.condition_keys.join(", ")
+        );
+    }
+    if !action.dependent_actions.is_empty() {
+        println!(
+            "{}[*] Dependent actions: {}",
+            indent,
+            action.dependent_actions.join(", ")
+        );
+    }
+}
+
+/// Represents the "describe-action" subcommand for looking up the details of actions.
+///
+/// This command prints the service, access level and any metadata provided by the catalog
+/// source (resource types, condition keys, dependent actions) of every matching action.
+#[derive(Debug, clap::Args)]
+pub struct DescribeActionSubCommand {
+    /// The action to describe (e.g., "iam:PassRole"). IAM wildcards (`*` or `?`) describe
+    /// every matching action (e.g., "iam:Pass*").
+    action: String,
+
+    /// The output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
+impl DescribeActionSubCommand {
+    /// Executes the describe-action subcommand.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The catalog of known AWS IAM services and actions.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command executes successfully
+    /// - `Err(Box<dyn std::error::Error>)` if no action matches or the output cannot be serialized
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
+        let descriptions = catalog.describe(&self.action);
+        if descriptions.is_empty() {
+            return Err(format!("No actions match '{}'", self.action).into());
+        }
+
+        match self.format {
+            OutputFormat::Text => {
+                println!(
+                    "[+] Describing AWS IAM actions matching '{}'...",
+                    self.action
+                );
+                for description in &descriptions {
+                    println!("\t[-] {}", description.action.name);
+                    println!(
+                        "\t\t[*] Service: {} ({})",
+                        description.service,
+                        description.service_names.join(", ")
+                    );
+                    println!("\t\t[*] Access level: {}", description.action.action_type);
+                    print_action_metadata(&description.action, "\t\t");
+                }
+            }
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&descriptions)?),
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, clap::Args)]
 pub struct ExpandFileSubCommand {
     /// Optional AWS file containing IAM policy to expand actions from.
@@ -99,19 +241,105 @@ pub struct ExpandFileSubCommand {
     /// This argument requires the `input_policy_file` argument to be specified.
     #[arg(long = "output-file", required = false, requires = "policy_file")]
     output_file: Option<String>,
+
+    /// The format of the policy file(s), overriding the detection from their extension
+    /// (`.yaml` and `.yml` files are read as YAML, any other file as JSON).
+    #[arg(long = "policy-format", value_enum)]
+    policy_format: Option<crate::policy::PolicyFormat>,
+
+    /// The format of the output. If not provided, it is detected from the extension of the
+    /// output file, or else the format of the policy file is kept.
+    ///
+    /// Limitation: YAML output only keeps the comment lines heading a YAML policy file.
+    /// Comments inside the document (e.g., next to a statement) are dropped, with a warning.
+    #[arg(long = "output-format", value_enum)]
+    output_format: Option<crate::policy::PolicyFormat>,
+
+    /// How full wildcards (`"Action": "*"`) are rewritten.
+    ///
+    /// `keep` leaves the wildcard untouched, `expand` replaces it with every action in the
+    /// catalog and `summary` replaces it with one `service:*` entry per service.
+    #[arg(long = "full-wildcard", value_enum, default_value_t = crate::expansion::WildcardMode::Keep)]
+    full_wildcard: crate::expansion::WildcardMode,
+
+    /// How service wildcards (e.g., `"s3:*"`) are rewritten.
+    ///
+    /// `keep` and `summary` leave the wildcard untouched (reporting how many actions it
+    /// covers), while `expand` replaces it with every action of the service.
+    #[arg(long = "service-wildcard", value_enum, default_value_t = crate::expansion::WildcardMode::Expand)]
+    service_wildcard: crate::expansion::WildcardMode,
+
+    /// How `NotAction` elements of `Allow` statements are rewritten.
+    ///
+    /// `expand` expands the excluded patterns, while `complement` replaces the `NotAction`
+    /// element with an `Action` element listing every action the statement effectively grants.
+    #[arg(long = "not-action-mode", value_enum, default_value_t = crate::expansion::NotActionMode::Expand)]
+    not_action_mode: crate::expansion::NotActionMode,
+
+    /// Service prefixes limiting the scope of `NotAction` complements (e.g., "iam,s3").
+    ///
+    /// If not provided, the complement is computed against the whole catalog. Only valid with
+    /// `--not-action-mode complement`.
+    #[arg(long = "complement-services", value_delimiter = ',')]
+    complement_services: Vec<String>,
+
+    /// Fail if any action pattern matches no known action.
+    ///
+    /// Unmatched patterns (typos, brand-new actions or unknown services) are always kept
+    /// verbatim and reported; in strict mode they also make the command fail.
+    #[arg(long)]
+    strict: bool,
+
+    /// Output an annotated expansion instead of the expanded policy.
+    ///
+    /// For each statement, every original pattern is listed with the actions it matched and
+    /// their count, and actions matched by more than one pattern are flagged as overlaps.
+    #[arg(long)]
+    annotate: bool,
+
+    /// Only keep actions of this access level (e.g., "read", "list", "write", "tagging",
+    /// "permissions-management"). Can be repeated.
+    ///
+    /// `NotAction` elements and `Deny` statements are expanded unfiltered, and statements
+    /// left without any action are dropped. While an access level filter is set, every wildcard is expanded.
+    #[arg(long = "access-level")]
+    access_levels: Vec<String>,
+
+    /// Drop actions of this access level (e.g., "write") from the `Action` element of `Allow`
+    /// statements. Can be repeated.
+    #[arg(long = "exclude-access-level")]
+    exclude_access_levels: Vec<String>,
+
+    /// Report, for each statement, how many expanded actions fall into each access level per
+    /// service, and which percentage of each service's actions the statement covers.
+    #[arg(long = "access-report")]
+    access_report: bool,
+
+    /// An earlier catalog file or directory, in the toolkit's own format, to look up actions
+    /// missing from the current catalog in. Can be repeated, oldest first.
+    ///
+    /// The caches archived by `update-cache` and the embedded catalog snapshot are always used.
+    #[arg(long = "previous-catalog")]
+    previous_catalogs: Vec<String>,
 }
 
 impl ExpandFileSubCommand {
     /// Executes the expand-file subcommand to expand actions in a given IAM policy file.
     ///
     /// This function reads the specified IAM policy file, expands any wildcard actions
-    /// using a trie built from available IAM actions, and outputs the expanded policy
-    /// either to the console or to a specified output file.
+    /// against the catalog of available IAM actions, and outputs the expanded policy
+    /// either to the console or to a specified output file. Full and service wildcards are
+    /// rewritten according to the `--full-wildcard` and `--service-wildcard` modes, and
+    /// each one handled this way is reported. With `--not-action-mode complement`, the
+    /// `NotAction` element of `Allow` statements is replaced with the actions it effectively grants.
+    /// With `--access-level` or `--exclude-access-level`, only actions of the selected access
+    /// levels are kept, and `--access-report` breaks each statement down per access level.
+    /// Deprecated and removed actions referenced by the policy are reported with their
+    /// known replacements.
     ///
     /// # Arguments
     ///
-    /// * `available_services_permissions` - A HashMap mapping service prefixes to their associated
-    ///  service and action data, typically populated from the AWS IAM actions JSON.
+    /// * `catalog` - The catalog of known AWS IAM services and actions.
     ///
     /// # Returns
     ///
@@ -121,119 +349,349 @@ impl ExpandFileSubCommand {
     /// # Errors
     ///
     /// This function will return an error if there are issues reading the policy file,
-    /// parsing the JSON content, or writing to the output file.
-    fn handle(
-        &self,
-        available_services_permissions: HashMap<String, Vec<crate::types::AwsService>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut policy_content = serde_json::from_str::<crate::types::AWSPolicyDocument>(
-            &std::fs::read_to_string(&self.policy_file)?,
-        )?;
-
-        let services_permissions: Vec<String> = available_services_permissions
-            .values()
-            .flatten()
-            .flat_map(|service| service.actions.iter().map(|action| action.name.to_string()))
-            .collect::<Vec<String>>();
-
-        let trie = trie_rs::Trie::from_iter(services_permissions);
-
-        for statement in &mut policy_content.statement {
-            statement.action = serde_json::Value::Array(
-                self.expand_actions(&statement.action, &trie)
-                    .into_iter()
-                    .map(serde_json::Value::String)
-                    .collect::<Vec<serde_json::Value>>(),
+    /// parsing the JSON content, or writing to the output file, if `--complement-services`
+    /// is given without `--not-action-mode complement`, or if `--strict` is set and an action
+    /// pattern matches no known action.
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.complement_services.is_empty()
+            && self.not_action_mode != crate::expansion::NotActionMode::Complement
+        {
+            return Err("--complement-services requires --not-action-mode complement".into());
+        }
+
+        let mut policy_content =
+            crate::utils::read_policy_file(&self.policy_file, self.policy_format)?;
+
+        let expander =
+            crate::expansion::Expander::new(catalog, self.full_wildcard, self.service_wildcard)
+                .with_access_level_filter(crate::expansion::AccessLevelFilter::new(
+                    &self.access_levels,
+                    &self.exclude_access_levels,
+                ))
+                .with_not_action_mode(self.not_action_mode, &self.complement_services);
+
+        // The history only adds to the report, so it never gets in the way of the expansion.
+        let mut history = crate::retired::ActionHistory::load().unwrap_or_else(|error| {
+            eprintln!(
+                "[!] Could not load the catalog history, removed actions are not reported: {}",
+                error
             );
+            crate::retired::ActionHistory::default()
+        });
+        for previous_catalog in &self.previous_catalogs {
+            let services = crate::source::CatalogSource::load(&crate::source::LocalSource {
+                path: previous_catalog.clone(),
+            })?;
+            history.add_services(previous_catalog, &services);
+        }
+
+        // Every statement is annotated, including the ones the expansion removes.
+        let statements = policy_content
+            .document
+            .statement
+            .iter()
+            .enumerate()
+            .map(|(statement_index, statement)| {
+                (
+                    statement_index,
+                    statement.sid.clone(),
+                    statement.effect.clone(),
+                )
+            })
+            .collect::<Vec<(usize, Option<String>, String)>>();
+        let expansion = expander.expand_document(&mut policy_content.document);
+
+        let mut retired: Vec<(String, crate::retired::RetiredAction)> = Vec::new();
+        for element in &expansion.elements {
+            let label =
+                crate::expansion::statement_label(element.statement_index, element.sid.as_deref());
+            self.report_element(&expander, element, &label);
+            retired.extend(Self::retired_actions(
+                &history,
+                &element.patterns,
+                catalog,
+                &label,
+                &element.field,
+            ));
+        }
+        self.report_removed(&policy_content.document, &statements, &expansion.removed)?;
 
-            if let Some(not_action) = &statement.not_action {
-                statement.not_action = Some(serde_json::Value::Array(
-                    self.expand_actions(not_action, &trie)
-                        .into_iter()
-                        .map(serde_json::Value::String)
-                        .collect::<Vec<serde_json::Value>>(),
-                ));
+        if self.access_report {
+            println!("[*] Access level breakdown:");
+            for element in &expansion.elements {
+                let field = if element.complement_scope.is_some() {
+                    "NotAction complement"
+                } else {
+                    element.field.as_str()
+                };
+                println!(
+                    "\t[+] {} {} {}:",
+                    crate::expansion::statement_label(
+                        element.statement_index,
+                        element.sid.as_deref()
+                    ),
+                    element.effect,
+                    field
+                );
+                crate::expansion::access_breakdown(&element.actions, catalog.index())
+                    .iter()
+                    .for_each(|service| println!("\t\t[-] {}", service));
             }
         }
 
-        self.output_results(&policy_content)
+        Self::report_retired(&retired);
+        self.report_unmatched(&expansion.unmatched)?;
+        let output_format = output_policy_format(
+            self.output_format,
+            self.output_file.as_deref(),
+            &policy_content,
+        );
+        if self.annotate {
+            let annotated = statements
+                .into_iter()
+                .map(
+                    |(statement_index, sid, effect)| crate::expansion::AnnotatedStatement {
+                        statement_index,
+                        sid,
+                        effect,
+                        elements: expansion
+                            .elements
+                            .iter()
+                            .filter(|element| element.statement_index == statement_index)
+                            .map(|element| {
+                                crate::expansion::annotate(&element.patterns, &element.field)
+                            })
+                            .collect::<Vec<crate::expansion::AnnotatedElement>>(),
+                    },
+                )
+                .collect::<Vec<crate::expansion::AnnotatedStatement>>();
+            self.output_results(
+                &output_format.render(&annotated)?,
+                output_format,
+                "Annotated Expansion",
+            )
+        } else {
+            self.output_results(
+                &render_policy(&policy_content, output_format, &self.policy_file)?,
+                output_format,
+                "Expanded Policy",
+            )
+        }
+    }
+
+    /// Reports how an `Action` or `NotAction` element was expanded.
+    ///
+    /// Full and service wildcards, rewritten according to the configured modes, and
+    /// `NotAction` elements rewritten as their complement are reported on the console, as
+    /// are the elements expanded without the access level filter while one is set (see
+    /// [`Expander::filters`](crate::expansion::Expander::filters)).
+    ///
+    /// # Arguments
+    ///
+    /// * `expander` - The expander holding the access level filter.
+    /// * `element` - The expanded element.
+    /// * `label` - The label identifying the statement in reports.
+    fn report_element(
+        &self,
+        expander: &crate::expansion::Expander,
+        element: &crate::expansion::ExpandedElement,
+        label: &str,
+    ) {
+        if let Some(total) = element.complement_scope {
+            let scope = if self.complement_services.is_empty() {
+                "the whole catalog".to_string()
+            } else {
+                format!("services {}", self.complement_services.join(", "))
+            };
+            println!(
+                "[*] {}: NotAction rewritten as its complement, granting {} of {} actions in {}",
+                label,
+                element.actions.len(),
+                total,
+                scope
+            );
+            return;
+        }
+
+        if expander.is_filtering() && !element.filtered {
+            println!(
+                "[*] {}: {} element of the {} statement expanded without the access level filter",
+                label, element.field, element.effect
+            );
+        }
+        // Unmatched patterns are reported separately by `report_unmatched`.
+        for pattern in element
+            .patterns
+            .iter()
+            .filter(|pattern| !pattern.actions.is_empty())
+        {
+            if let Some((kind, mode)) = &pattern.wildcard {
+                let kind = match kind {
+                    crate::expansion::WildcardKind::Full => "full wildcard",
+                    crate::expansion::WildcardKind::Service(_) => "service wildcard",
+                };
+                let handling = match mode {
+                    crate::expansion::WildcardMode::Keep => {
+                        format!("kept (covers {} actions)", pattern.actions.len())
+                    }
+                    crate::expansion::WildcardMode::Expand => {
+                        format!("expanded into {} actions", pattern.replacement.len())
+                    }
+                    crate::expansion::WildcardMode::Summary => format!(
+                        "summarised as {} service wildcard(s) (covers {} actions)",
+                        pattern.replacement.len(),
+                        pattern.actions.len()
+                    ),
+                };
+                println!(
+                    "[*] {}: {} {} '{}' {}",
+                    label, element.field, kind, pattern.pattern, handling
+                );
+            }
+        }
     }
 
-    /// Expands actions from a serde_json::Value using the provided trie.
+    /// Reports the statements removed for granting no action once expanded.
     ///
-    /// Supports both string and array formats for actions.
+    /// An `Allow` statement whose actions are all filtered out by access level, or whose
+    /// `NotAction` complement is empty within the complemented services, grants nothing, and
+    /// an empty `Action` element would be rejected by IAM, so the statement is dropped.
     ///
     /// # Arguments
     ///
-    /// * `action_value` - The serde_json::Value representing the action(s) to expand.
-    /// * `trie` - The trie containing all available IAM actions for prefix searching.
+    /// * `document` - The expanded policy document.
+    /// * `statements` - The index, Sid and effect of every statement of the original policy.
+    /// * `removed` - The indexes of the removed statements.
     ///
     /// # Returns
     ///
-    /// A vector of expanded action strings.
-    fn expand_actions(
+    /// - `Ok(())` if the document still has statements
+    /// - `Err(Box<dyn std::error::Error>)` if every statement was dropped
+    fn report_removed(
         &self,
-        action_value: &serde_json::Value,
-        trie: &trie_rs::Trie<u8>,
-    ) -> Vec<String> {
-        match action_value {
-            serde_json::Value::String(action) => self.expand_string_actions(action, trie),
-            serde_json::Value::Array(actions) => self.expand_array_actions(actions, trie),
-            _ => {
-                eprintln!("[!] Unsupported action format in policy.");
-                Vec::new()
-            }
+        document: &crate::types::AWSPolicyDocument,
+        statements: &[(usize, Option<String>, String)],
+        removed: &[usize],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        statements
+            .iter()
+            .filter(|(statement_index, _, _)| removed.contains(statement_index))
+            .for_each(|(statement_index, sid, _)| {
+                eprintln!(
+                    "[!] {}: no action left to grant once expanded, statement dropped",
+                    crate::expansion::statement_label(*statement_index, sid.as_deref())
+                )
+            });
+
+        if document.statement.is_empty() {
+            return Err(format!(
+                "No statement of '{}' grants any action once expanded",
+                self.policy_file
+            )
+            .into());
         }
+        Ok(())
     }
 
-    /// Expands actions from a string using the provided trie.
+    /// Finds the deprecated and removed actions referenced by the patterns of an element.
+    ///
+    /// Concrete actions are always looked up, while wildcard patterns are only looked up
+    /// when they match no action of the current catalog.
     ///
     /// # Arguments
     ///
-    /// * `action_str` - The action string to expand.
-    /// * `trie` - The trie containing all available IAM actions for prefix searching.
+    /// * `history` - The actions seen in earlier catalogs.
+    /// * `expanded` - The expanded patterns of the element.
+    /// * `catalog` - The catalog of known AWS IAM services and actions.
+    /// * `label` - The label identifying the statement in reports.
+    /// * `field` - The name of the policy element ("Action" or "NotAction").
+    ///
     /// # Returns
     ///
-    /// A vector of expanded action strings.
-    fn expand_string_actions(&self, action_str: &str, trie: &trie_rs::Trie<u8>) -> Vec<String> {
-        trie.predictive_search(action_str.replace('*', ""))
-            .collect::<Vec<String>>()
+    /// The retired actions, each with the statement label and element referencing it.
+    fn retired_actions(
+        history: &crate::retired::ActionHistory,
+        expanded: &[crate::expansion::ExpandedPattern],
+        catalog: &crate::catalog::Catalog,
+        label: &str,
+        field: &str,
+    ) -> Vec<(String, crate::retired::RetiredAction)> {
+        expanded
+            .iter()
+            .filter(|pattern| {
+                !crate::pattern::has_wildcard(&pattern.pattern) || pattern.actions.is_empty()
+            })
+            .flat_map(|pattern| history.retired(&pattern.pattern, catalog))
+            .map(|action| (format!("{}: {}", label, field), action))
+            .collect::<Vec<(String, crate::retired::RetiredAction)>>()
+    }
+
+    /// Reports the deprecated and removed actions referenced by the policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `retired` - The retired actions, each with the statement label and element referencing it.
+    fn report_retired(retired: &[(String, crate::retired::RetiredAction)]) {
+        if retired.is_empty() {
+            return;
+        }
+
+        eprintln!(
+            "[!] {} deprecated or removed action(s) referenced:",
+            retired.len()
+        );
+        retired
+            .iter()
+            .for_each(|(element, action)| eprintln!("\t[!] {} {}", element, action));
     }
 
-    /// Expands actions from an array of serde_json::Value using the provided trie.
+    /// Reports the action patterns that matched no known action.
+    ///
+    /// Each unmatched pattern is listed with its statement index and Sid. Unmatched patterns
+    /// are kept verbatim in the expanded policy so that it never grants less than the original.
     ///
     /// # Arguments
     ///
-    /// * `action_array` - The array of serde_json::Value representing the actions to expand.
-    /// * `trie` - The trie containing all available IAM actions for prefix searching.
+    /// * `unmatched` - The unmatched patterns found in the policy.
     ///
     /// # Returns
     ///
-    /// A vector of expanded action strings.
-    fn expand_array_actions(
+    /// - `Ok(())` if there are no unmatched patterns or strict mode is disabled
+    /// - `Err(Box<dyn std::error::Error>)` if strict mode is enabled and patterns are unmatched
+    fn report_unmatched(
         &self,
-        action_array: &Vec<serde_json::Value>,
-        trie: &trie_rs::Trie<u8>,
-    ) -> Vec<String> {
-        action_array
+        unmatched: &[crate::expansion::UnmatchedPattern],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if unmatched.is_empty() {
+            return Ok(());
+        }
+
+        eprintln!(
+            "[!] {} action pattern(s) matched no known actions and were kept verbatim:",
+            unmatched.len()
+        );
+        unmatched
             .iter()
-            .flat_map(|action_value| {
-                if let serde_json::Value::String(action) = action_value {
-                    self.expand_string_actions(action, trie)
-                } else {
-                    Vec::new()
-                }
-            })
-            .collect::<std::collections::HashSet<String>>()
-            .into_iter()
-            .collect::<Vec<String>>()
+            .for_each(|pattern| eprintln!("\t[!] {}", pattern));
+
+        if self.strict {
+            return Err(format!(
+                "{} action pattern(s) in '{}' matched no known actions",
+                unmatched.len(),
+                self.policy_file
+            )
+            .into());
+        }
+        Ok(())
     }
 
-    /// Outputs the expanded policy results to either a file or the console.
+    /// Outputs the expansion results to either a file or the console.
     ///
     /// # Arguments
     ///
-    /// * `policy_content` - The expanded AWS IAM policy document to output.
+    /// * `results` - The serialized expanded AWS IAM policy document or annotated expansion.
+    /// * `format` - The format the results are serialized in.
+    /// * `description` - A title-cased description of the results used in console messages.
     ///
     /// # Returns
     ///
@@ -241,39 +699,324 @@ impl ExpandFileSubCommand {
     /// - `Err(Box<dyn std::error::Error>)` if an error occurs during output operation
     fn output_results(
         &self,
-        policy_content: &crate::types::AWSPolicyDocument,
+        results: &str,
+        format: crate::policy::PolicyFormat,
+        description: &str,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        if self.output_file.is_some() {
+        if let Some(output_file) = &self.output_file {
             println!(
-                "[+] Writing expanded policy to file: {}",
-                self.output_file.as_ref().unwrap()
+                "[+] Writing {} to file: {}",
+                description.to_lowercase(),
+                output_file
             );
-            std::fs::write(
-                self.output_file.as_ref().unwrap(),
-                serde_json::to_string_pretty(&policy_content)?,
-            )?;
+            std::fs::write(output_file, results)?;
+        } else if format == crate::policy::PolicyFormat::Yaml {
+            print!("[*] {}:\n{}", description, results);
         } else {
+            println!("[*] {}: {}", description, results);
+        }
+        Ok(())
+    }
+}
+
+/// Represents the "who-grants" subcommand for finding the statements covering an action.
+///
+/// This command performs a reverse lookup: given concrete actions and policy files, it reports
+/// every statement whose `Action` or `NotAction` patterns cover each action.
+#[derive(Debug, clap::Args)]
+pub struct WhoGrantsSubCommand {
+    /// The concrete action to look up (e.g., "iam:PassRole"). Can be repeated.
+    #[arg(long = "action", required = true)]
+    actions: Vec<String>,
+
+    /// The policy file to search. Can be repeated.
+    #[arg(long = "policy-file", required = true)]
+    policy_files: Vec<String>,
+
+    /// The format of the policy file(s), overriding the detection from their extension
+    /// (`.yaml` and `.yml` files are read as YAML, any other file as JSON).
+    #[arg(long = "policy-format", value_enum)]
+    policy_format: Option<crate::policy::PolicyFormat>,
+}
+
+impl WhoGrantsSubCommand {
+    /// Executes the who-grants subcommand.
+    ///
+    /// Every policy file is parsed, and each action is matched against the `Action` and
+    /// `NotAction` patterns of every statement. Covering statements are reported by file,
+    /// index and Sid, together with their effect and the responsible patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The catalog of known AWS IAM services and actions, whose `ActionIndex`
+    ///   resolves the canonical name of each looked-up action. Actions missing from the
+    ///   catalog are looked up as given, with a warning.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command executes successfully
+    /// - `Err(Box<dyn std::error::Error>)` if an error occurs during execution
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a policy file cannot be read or parsed.
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
+        let policies = self
+            .policy_files
+            .iter()
+            .map(|policy_file| {
+                crate::utils::read_policy_file(policy_file, self.policy_format)
+                    .map(|policy| (policy_file, policy.document))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for action in &self.actions {
+            let action = match catalog.index().canonical_name(action) {
+                Some(canonical) => canonical.to_string(),
+                None => {
+                    eprintln!(
+                        "[!] Warning: Action '{}' is not in the AWS IAM actions catalog.",
+                        action
+                    );
+                    action.clone()
+                }
+            };
+
+            println!("[+] Statements granting or denying '{}':", action);
+            let grants = policies
+                .iter()
+                .flat_map(|(policy_file, policy)| {
+                    crate::grants::find_grants(policy_file, policy, &action)
+                })
+                .collect::<Vec<crate::grants::StatementGrant>>();
+
+            if grants.is_empty() {
+                println!("\t[-] No statement covers this action.");
+            }
+            grants.iter().for_each(|grant| println!("\t[-] {}", grant));
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the "compress" subcommand for rewriting actions into minimal wildcards.
+///
+/// This command is the inverse of the expansion: it produces the smallest set of
+/// `service:Prefix*` patterns matching exactly the given actions and nothing else in the
+/// current catalog, either for a list of actions or for every statement of a policy file.
+#[derive(Debug, clap::Args)]
+#[command(group(clap::ArgGroup::new("input").required(true).args(["actions", "policy_file"])))]
+pub struct CompressSubCommand {
+    /// The actions to compress (e.g., "iam:CreateRole,iam:CreateUser"). Can be repeated.
+    #[arg(long = "action", value_delimiter = ',')]
+    actions: Vec<String>,
+
+    /// The IAM policy file whose `Action` elements of `Allow` statements are compressed.
+    #[arg(long = "policy-file")]
+    policy_file: Option<String>,
+
+    /// Optional output file to save the compressed policy.
+    #[arg(long = "output-file", requires = "policy_file")]
+    output_file: Option<String>,
+
+    /// The format of the policy file(s), overriding the detection from their extension
+    /// (`.yaml` and `.yml` files are read as YAML, any other file as JSON).
+    #[arg(long = "policy-format", value_enum)]
+    policy_format: Option<crate::policy::PolicyFormat>,
+
+    /// The format of the output. If not provided, it is detected from the extension of the
+    /// output file, or else the format of the policy file is kept.
+    ///
+    /// Limitation: YAML output only keeps the comment lines heading a YAML policy file.
+    /// Comments inside the document (e.g., next to a statement) are dropped, with a warning.
+    #[arg(long = "output-format", value_enum)]
+    output_format: Option<crate::policy::PolicyFormat>,
+}
+
+impl CompressSubCommand {
+    /// Executes the compress subcommand.
+    ///
+    /// With `--action`, the compressed patterns are printed. With `--policy-file`, the
+    /// `Action` element of every `Allow` statement is expanded and compressed, and the
+    /// rewritten policy is output either to the console or to the specified output file.
+    ///
+    /// A compressed pattern also matches the actions AWS later adds under its prefix. This
+    /// is the intended trade-off for the actions a statement allows, but in a `NotAction`
+    /// element or a `Deny` statement it would silently change what is exempted or denied,
+    /// so these elements are left untouched, with a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The catalog of known AWS IAM services and actions.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command executes successfully
+    /// - `Err(Box<dyn std::error::Error>)` if an error occurs during execution
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the policy file cannot be read or parsed, or if
+    /// the output file cannot be written.
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(policy_file) = &self.policy_file else {
+            let compressed = crate::compress::compress_actions(&self.actions, catalog.index());
             println!(
-                "[*] Expanded Policy: {}",
-                serde_json::to_string_pretty(&policy_content)?
+                "[+] Compressed {} action(s) into {} pattern(s):",
+                self.actions.len(),
+                compressed.len()
             );
+            compressed
+                .iter()
+                .for_each(|pattern| println!("\t[-] {}", pattern));
+            return Ok(());
+        };
+
+        let mut policy_content = crate::utils::read_policy_file(policy_file, self.policy_format)?;
+        let original_size = serde_json::to_string(&policy_content.document)?.len();
+
+        for (statement_index, statement) in policy_content.document.statement.iter_mut().enumerate()
+        {
+            let label =
+                crate::expansion::statement_label(statement_index, statement.sid.as_deref());
+
+            for (field, element) in [
+                ("Action", &mut statement.action),
+                ("NotAction", &mut statement.not_action),
+            ] {
+                let Some(action_value) = element else {
+                    continue;
+                };
+                if statement.effect != "Allow" || field != "Action" {
+                    eprintln!(
+                        "[!] Warning: {}: {} element of the {} statement left uncompressed",
+                        label, field, statement.effect
+                    );
+                    continue;
+                }
+                let actions = action_value.iter().cloned().collect::<Vec<String>>();
+
+                let compressed = crate::compress::compress_actions(&actions, catalog.index());
+                println!(
+                    "[*] {}: {} compressed from {} to {} entries",
+                    label,
+                    field,
+                    actions.len(),
+                    compressed.len()
+                );
+                *element = Some(rewritten_element(action_value, compressed));
+            }
+        }
+
+        let output_format = output_policy_format(
+            self.output_format,
+            self.output_file.as_deref(),
+            &policy_content,
+        );
+        let compressed_policy = render_policy(&policy_content, output_format, policy_file)?;
+        println!(
+            "[*] Policy size (without whitespace): {} -> {} characters",
+            original_size,
+            serde_json::to_string(&policy_content.document)?.len()
+        );
+        match &self.output_file {
+            Some(output_file) => {
+                println!("[+] Writing compressed policy to file: {}", output_file);
+                std::fs::write(output_file, compressed_policy)?;
+            }
+            None if output_format == crate::policy::PolicyFormat::Yaml => {
+                print!("[*] Compressed Policy:\n{}", compressed_policy)
+            }
+            None => println!("[*] Compressed Policy: {}", compressed_policy),
         }
         Ok(())
     }
 }
 
+/// Builds the rewritten value of an `Action` or `NotAction` element.
+///
+/// The original element is kept as is when its entries did not change, so that the output
+/// only differs from the policy where actions changed.
+///
+/// # Arguments
+///
+/// * `original` - The element as written in the policy.
+/// * `entries` - The rewritten action strings.
+///
+/// # Returns
+///
+/// The original element if its entries are unchanged, or an array of the rewritten entries.
+fn rewritten_element(
+    original: &crate::types::OneOrMany<String>,
+    entries: Vec<String>,
+) -> crate::types::OneOrMany<String> {
+    if original.iter().eq(entries.iter()) {
+        original.clone()
+    } else {
+        crate::types::OneOrMany::from(entries)
+    }
+}
+
+/// Resolves the format a policy command writes its output in.
+///
+/// # Arguments
+///
+/// * `requested` - The format given with `--output-format`, if any.
+/// * `output_file` - The output file, if any, whose extension selects the format otherwise.
+/// * `policy` - The policy file, whose format is kept if neither selects one.
+///
+/// # Returns
+///
+/// The format of the output.
+fn output_policy_format(
+    requested: Option<crate::policy::PolicyFormat>,
+    output_file: Option<&str>,
+    policy: &crate::policy::PolicyFile,
+) -> crate::policy::PolicyFormat {
+    requested
+        .or_else(|| output_file.and_then(crate::policy::PolicyFormat::from_extension))
+        .unwrap_or(policy.format)
+}
+
+/// Serializes a rewritten policy, warning about the YAML comments that cannot be kept.
+///
+/// # Arguments
+///
+/// * `policy` - The rewritten policy file.
+/// * `format` - The format to write the policy in.
+/// * `policy_file` - The path to the policy file, used in the warning.
+///
+/// # Returns
+///
+/// A `Result` containing the serialized policy, or an error if it cannot be serialized.
+fn render_policy(
+    policy: &crate::policy::PolicyFile,
+    format: crate::policy::PolicyFormat,
+    policy_file: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if format == crate::policy::PolicyFormat::Yaml && policy.inner_comments > 0 {
+        eprintln!(
+            "[!] Warning: {} comment line(s) inside '{}' cannot be kept in the rewritten policy.",
+            policy.inner_comments, policy_file
+        );
+    }
+    policy.render(format)
+}
+
 /// Enumeration of available CLI subcommands.
 ///
 /// This enum represents the different actions the user can perform with the toolkit.
 /// Each variant corresponds to a specific operation that can be invoked from the command line.
 #[derive(Debug, Subcommand)]
 pub enum Action {
-    /// List all available AWS services and their service prefixes.
+    /// List the available AWS services, optionally searching by prefix or full name.
     ///
     /// This command displays every AWS service available in the IAM actions database,
-    /// showing the service prefix that can be used with the expand command.
+    /// sorted by the service prefix that can be used with the expand command, with its full
+    /// name and the number of actions per access level.
     #[command(name = "list-services")]
-    ListServices,
+    ListServices(ListServicesSubCommand),
 
     /// Expand AWS IAM actions based on a service name and optional action prefix.
     ///
@@ -288,6 +1031,55 @@ pub enum Action {
     #[command(name = "expand-file")]
     ExpandFile(ExpandFileSubCommand),
 
+    /// Describe one or more actions.
+    ///
+    /// This command prints the service, full service name, access level, resource types,
+    /// condition keys and dependent actions of every action matching a name or pattern.
+    #[command(name = "describe-action")]
+    DescribeAction(DescribeActionSubCommand),
+
+    /// Find the policy statements covering one or more concrete actions.
+    ///
+    /// This command reports every statement, by file, index and Sid, whose `Action` or
+    /// `NotAction` patterns cover each action, together with its effect and matching patterns.
+    #[command(name = "who-grants")]
+    WhoGrants(WhoGrantsSubCommand),
+
+    /// Compress actions into the smallest set of wildcards matching exactly those actions.
+    ///
+    /// This command rewrites a list of actions, or the actions of a policy file, into the
+    /// minimal set of `service:Prefix*` patterns that grants nothing else in the catalog.
+    Compress(CompressSubCommand),
+
+    /// Compare two catalog snapshots.
+    ///
+    /// This command reports the services and actions added or removed between two
+    /// catalogs, and the actions whose access level changed, as text or JSON.
+    #[command(name = "catalog-diff")]
+    CatalogDiff(CatalogDiffSubCommand),
+
+    /// Detect wildcard drift in policies across catalog versions.
+    ///
+    /// This command reports the concrete actions each wildcard of a policy grants under a
+    /// newer catalog that it did not grant under an older one, grouped by statement and
+    /// access level.
+    Drift(DriftSubCommand),
+
+    /// Expand the policies embedded in CloudFormation templates.
+    ///
+    /// This command finds the IAM and resource policies of JSON and YAML templates,
+    /// resolves their intrinsic functions, and reports each expanded policy by the logical
+    /// ID of its resource.
+    #[command(name = "scan-template")]
+    ScanTemplate(ScanTemplateSubCommand),
+
+    /// Expand the policies of Terraform plans.
+    ///
+    /// This command decodes the policies of the resources of plans rendered by
+    /// `terraform show -json`, and reports each expanded policy by its resource address.
+    #[command(name = "scan-plan")]
+    ScanPlan(ScanPlanSubCommand),
+
     /// Delete cached data files used by the toolkit.
     ///
     /// This command removes any locally stored cache files to free up space
@@ -296,24 +1088,747 @@ pub enum Action {
 
     /// Update the cached AWS IAM actions data.
     ///
-    /// This command fetches the latest AWS IAM actions from the API
-    /// and updates the local cache.
+    /// This command fetches the latest AWS IAM actions from the configured AWS IAM Actions
+    /// API URL and updates the local cache, archiving the previous one with its source.
     #[command(name = "update-cache")]
     UpdateCache,
+
+    /// Print the date of the embedded catalog snapshot and compare it with the cache.
+    ///
+    /// The snapshot is only available when the toolkit is built with the
+    /// `embedded-catalog` feature.
+    #[command(name = "snapshot-info")]
+    SnapshotInfo,
 }
 
-/// Represents the top-level command-line arguments and options.
+/// Represents the "list-services" subcommand for discovering AWS services.
 ///
-/// This struct is the entry point for argument parsing and contains the subcommand
-/// that the user wishes to execute.
-#[derive(Debug, clap::Parser)]
+/// This command lists the services of the catalog, sorted by prefix, with the full names of
+/// the services sharing each prefix and the number of actions per access level, optionally
+/// filtered by a search query. Actions listed with different access levels by services
+/// sharing a prefix are flagged.
+#[derive(Debug, clap::Args)]
+pub struct ListServicesSubCommand {
+    /// Only list services whose prefix or full name matches this query, by substring or
+    /// fuzzily (e.g., "simple storage", "cognito sync").
+    #[arg(long)]
+    search: Option<String>,
+}
+
+impl ListServicesSubCommand {
+    /// Executes the list-services subcommand.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The catalog of known AWS IAM services and actions.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command executes successfully
+    /// - `Err(Box<dyn std::error::Error>)` if an error occurs during execution
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
+        let services = match &self.search {
+            Some(query) => {
+                println!("[*] Searching AWS IAM services matching '{}'", query);
+                catalog.search(query)
+            }
+            None => {
+                println!("[*] Listing AWS IAM services");
+                catalog
+                    .services()
+                    .collect::<Vec<&crate::types::AwsService>>()
+            }
+        };
+
+        if services.is_empty() {
+            println!("[!] No matching services found.");
+        }
+        for service in services {
+            // Access levels spelled differently by the sources (e.g., "Permissions management"
+            // and "permissions-management") are counted together, under the first spelling.
+            let mut access_levels: Vec<(String, String, usize)> = Vec::new();
+            for action in &service.actions {
+                let normalized = crate::expansion::normalize_access_level(&action.action_type);
+                match access_levels
+                    .iter_mut()
+                    .find(|(access_level, _, _)| *access_level == normalized)
+                {
+                    Some((_, _, count)) => *count += 1,
+                    None => access_levels.push((normalized, action.action_type.clone(), 1)),
+                }
+            }
+            access_levels.sort_by_key(|(_, access_level, _)| {
+                crate::expansion::access_level_order(access_level)
+            });
+
+            println!(
+                "\t[+] {}: {} ({} action(s): {})",
+                service.prefix,
+                catalog.service_names(&service.prefix).join(", "),
+                service.actions.len(),
+                access_levels
+                    .iter()
+                    .map(|(_, access_level, count)| format!("{} {}", count, access_level))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+
+            let action_prefix = format!("{}:", service.prefix.to_lowercase());
+            for conflict in catalog
+                .conflicts()
+                .iter()
+                .filter(|conflict| conflict.action.to_lowercase().starts_with(&action_prefix))
+            {
+                println!(
+                    "\t\t[!] Conflicting access levels for '{}': {}",
+                    conflict.action,
+                    conflict
+                        .entries
+                        .iter()
+                        .map(|entry| format!("{} ({})", entry.access_level, entry.service_name))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The output formats of reporting commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text.
+    Text,
+
+    /// Pretty-printed JSON.
+    Json,
+}
+
+/// Represents the "catalog-diff" subcommand for comparing two catalog snapshots.
+///
+/// This command reports the services and actions added or removed between two catalogs,
+/// and the actions whose access level changed, as a changelog.
+#[derive(Debug, clap::Args)]
+pub struct CatalogDiffSubCommand {
+    /// The older catalog: a JSON file (or directory of JSON files) in the AWS IAM actions
+    /// format, such as a copy of the cache.
+    #[arg(long)]
+    old: String,
+
+    /// The newer catalog, in the same format as `--old`.
+    ///
+    /// If not provided, the catalog loaded from the selected catalog source (by default,
+    /// the cache) is used.
+    #[arg(long)]
+    new: Option<String>,
+
+    /// The output format of the changelog.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Optional output file to save the changelog.
+    #[arg(long = "output-file")]
+    output_file: Option<String>,
+}
+
+impl CatalogDiffSubCommand {
+    /// Executes the catalog-diff subcommand.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The catalog of known AWS IAM services and actions, compared with `--old`
+    ///   when `--new` is not provided.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command executes successfully
+    /// - `Err(Box<dyn std::error::Error>)` if an error occurs during execution
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a catalog file cannot be read or parsed, or if
+    /// the output file cannot be written.
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
+        let old = load_local_catalog(&self.old)?;
+        let loaded;
+        let new = match &self.new {
+            Some(path) => {
+                loaded = load_local_catalog(path)?;
+                &loaded
+            }
+            None => catalog,
+        };
+
+        let diff = crate::diff::diff_catalogs(&old, new);
+        let changelog = match self.format {
+            OutputFormat::Text => diff.to_string(),
+            OutputFormat::Json => serde_json::to_string_pretty(&diff)?,
+        };
+
+        if let Some(output_file) = &self.output_file {
+            println!("[+] Writing catalog diff to file: {}", output_file);
+            std::fs::write(output_file, changelog)?;
+        } else {
+            print!("{}", changelog);
+            if self.format == OutputFormat::Json {
+                println!();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Represents the "drift" subcommand for detecting wildcard drift across catalog versions.
+///
+/// This command reports, for every statement of the given policies, the concrete actions
+/// each wildcard grants under a newer catalog that it did not grant under an older one.
+#[derive(Debug, clap::Args)]
+pub struct DriftSubCommand {
+    /// The policy file to check. Can be repeated.
+    #[arg(long = "policy-file", required = true)]
+    policy_files: Vec<String>,
+
+    /// The older catalog: a JSON file (or directory of JSON files) in the AWS IAM actions
+    /// format, such as a copy of the cache.
+    #[arg(long)]
+    old: String,
+
+    /// The newer catalog, in the same format as `--old`.
+    ///
+    /// If not provided, the catalog loaded from the selected catalog source (by default,
+    /// the cache) is used.
+    #[arg(long)]
+    new: Option<String>,
+
+    /// The output format of the report.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Exit with an error if any wildcard of an `Allow` statement grants new actions, e.g.,
+    /// to fail a CI job. Actions newly denied by `Deny` statements never fail the command.
+    #[arg(long = "fail-on-drift")]
+    fail_on_drift: bool,
+
+    /// The format of the policy file(s), overriding the detection from their extension
+    /// (`.yaml` and `.yml` files are read as YAML, any other file as JSON).
+    #[arg(long = "policy-format", value_enum)]
+    policy_format: Option<crate::policy::PolicyFormat>,
+}
+
+impl DriftSubCommand {
+    /// Executes the drift subcommand.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The catalog of known AWS IAM services and actions, used as the newer
+    ///   catalog when `--new` is not provided.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command executes successfully
+    /// - `Err(Box<dyn std::error::Error>)` if an error occurs during execution
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a policy or catalog file cannot be read or
+    /// parsed, or if `--fail-on-drift` is set and a wildcard of an `Allow` statement grants
+    /// new actions. New actions covered by `Deny` statements are reported as newly denied,
+    /// without failing.
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
+        let old = load_local_catalog(&self.old)?;
+        let loaded;
+        let new = match &self.new {
+            Some(path) => {
+                loaded = load_local_catalog(path)?;
+                &loaded
+            }
+            None => catalog,
+        };
+
+        let mut drift: Vec<crate::drift::StatementDrift> = Vec::new();
+        for policy_file in &self.policy_files {
+            let policy = crate::utils::read_policy_file(policy_file, self.policy_format)?;
+            drift.extend(crate::drift::detect_drift(
+                policy_file,
+                &policy.document,
+                &old,
+                new,
+            ));
+        }
+
+        match self.format {
+            OutputFormat::Text if drift.is_empty() => {
+                println!("[+] No wildcard drift found.")
+            }
+            OutputFormat::Text => drift.iter().for_each(|statement| print!("{}", statement)),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&drift)?),
+        }
+
+        // Deny statements covering new actions only make the policies stricter.
+        let granting = drift.iter().filter(|statement| statement.grants()).count();
+        if self.fail_on_drift && granting > 0 {
+            return Err(format!(
+                "{} statement(s) grant new actions under the newer catalog",
+                granting
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Represents the "scan-template" subcommand for expanding the policies of CloudFormation
+/// templates.
+///
+/// This command finds every policy document embedded in the given templates, resolves its
+/// intrinsic functions, and expands it, reporting each policy by the logical ID of its
+/// resource.
+#[derive(Debug, clap::Args)]
+pub struct ScanTemplateSubCommand {
+    /// The CloudFormation template to scan, in JSON or YAML. Can be repeated.
+    #[arg(long = "template", required = true)]
+    templates: Vec<String>,
+
+    /// The format of the template(s), overriding the detection from their extension
+    /// (`.json` files are read as JSON, any other file as YAML).
+    #[arg(long = "template-format", value_enum)]
+    template_format: Option<crate::policy::PolicyFormat>,
+
+    /// The output format of the report.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Fail if any action pattern matches no known action.
+    #[arg(long)]
+    strict: bool,
+}
+
+impl ScanTemplateSubCommand {
+    /// Executes the scan-template subcommand.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The catalog of known AWS IAM services and actions.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command executes successfully
+    /// - `Err(Box<dyn std::error::Error>)` if an error occurs during execution
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a template or one of its policy documents
+    /// cannot be read or parsed, or if `--strict` is set and an action pattern matches no
+    /// known action.
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
+        let expander = crate::expansion::Expander::new(
+            catalog,
+            crate::expansion::WildcardMode::Keep,
+            crate::expansion::WildcardMode::Expand,
+        );
+
+        let mut scanned: Vec<ScannedPolicy<crate::cloudformation::TemplatePolicy>> = Vec::new();
+        for template_file in &self.templates {
+            let format = self
+                .template_format
+                .or_else(|| crate::policy::PolicyFormat::from_extension(template_file))
+                .unwrap_or(crate::policy::PolicyFormat::Yaml);
+            let template_source = std::fs::read_to_string(template_file).map_err(|error| {
+                format!("Could not read template '{}': {}", template_file, error)
+            })?;
+            let template = crate::cloudformation::parse_template(&template_source, format)
+                .map_err(|error| {
+                    format!("Could not parse template '{}': {}", template_file, error)
+                })?;
+            let policies = crate::cloudformation::template_policies(&template)
+                .map_err(|error| format!("{}: {}", template_file, error))?;
+
+            if policies.is_empty() {
+                eprintln!("[!] No policy documents found in '{}'.", template_file);
+            }
+            for mut policy in policies {
+                let unmatched = expander.expand_document(&mut policy.document).unmatched;
+                scanned.push(ScannedPolicy {
+                    source: template_file.clone(),
+                    policy,
+                    unmatched,
+                });
+            }
+        }
+
+        report_scanned_policies(&scanned, self.format, self.strict)
+    }
+}
+
+/// Represents the "scan-plan" subcommand for expanding the policies of Terraform plans.
+///
+/// This command decodes the policy attributes of the resources of plans rendered by
+/// `terraform show -json`, and expands them, reporting each policy by its resource address.
+#[derive(Debug, clap::Args)]
+pub struct ScanPlanSubCommand {
+    /// The Terraform plan to scan, as rendered by `terraform show -json`. Can be repeated.
+    #[arg(long = "plan", required = true)]
+    plans: Vec<String>,
+
+    /// The output format of the report.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Fail if any action pattern matches no known action.
+    #[arg(long)]
+    strict: bool,
+}
+
+impl ScanPlanSubCommand {
+    /// Executes the scan-plan subcommand.
+    ///
+    /// Policies only known after apply cannot be analyzed and are reported as warnings.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The catalog of known AWS IAM services and actions.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command executes successfully
+    /// - `Err(Box<dyn std::error::Error>)` if an error occurs during execution
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a plan or one of its policies cannot be read or
+    /// parsed, or if `--strict` is set and an action pattern matches no known action.
+    fn handle(&self, catalog: &crate::catalog::Catalog) -> Result<(), Box<dyn std::error::Error>> {
+        let expander = crate::expansion::Expander::new(
+            catalog,
+            crate::expansion::WildcardMode::Keep,
+            crate::expansion::WildcardMode::Expand,
+        );
+
+        let mut scanned: Vec<ScannedPolicy<crate::terraform::PlanPolicy>> = Vec::new();
+        for plan_file in &self.plans {
+            let plan_json = std::fs::read_to_string(plan_file)
+                .map_err(|error| format!("Could not read plan '{}': {}", plan_file, error))?;
+            let plan = serde_json::from_str::<serde_json::Value>(&plan_json)
+                .map_err(|error| format!("Could not parse plan '{}': {}", plan_file, error))?;
+            let scan = crate::terraform::plan_policies(&plan)
+                .map_err(|error| format!("{}: {}", plan_file, error))?;
+
+            scan.unknown.iter().for_each(|attribute| {
+                eprintln!(
+                    "[!] Warning: {}: {} is only known after apply and cannot be analyzed.",
+                    plan_file, attribute
+                )
+            });
+            if scan.policies.is_empty() {
+                eprintln!("[!] No policy documents found in '{}'.", plan_file);
+            }
+            for mut policy in scan.policies {
+                let unmatched = expander.expand_document(&mut policy.document).unmatched;
+                scanned.push(ScannedPolicy {
+                    source: plan_file.clone(),
+                    policy,
+                    unmatched,
+                });
+            }
+        }
+
+        report_scanned_policies(&scanned, self.format, self.strict)
+    }
+}
+
+/// A policy document embedded in an infrastructure definition, identified by its resource.
+trait EmbeddedPolicy: serde::Serialize + std::fmt::Display {
+    /// Returns the policy document.
+    fn document(&self) -> &crate::types::AWSPolicyDocument;
+}
+
+impl EmbeddedPolicy for crate::cloudformation::TemplatePolicy {
+    fn document(&self) -> &crate::types::AWSPolicyDocument {
+        &self.document
+    }
+}
+
+impl EmbeddedPolicy for crate::terraform::PlanPolicy {
+    fn document(&self) -> &crate::types::AWSPolicyDocument {
+        &self.document
+    }
+}
+
+/// The expansion of a policy document embedded in an infrastructure definition.
+#[derive(Debug, serde::Serialize)]
+struct ScannedPolicy<T> {
+    /// The file the policy was found in.
+    source: String,
+
+    /// The policy, identified by its resource, with its expanded document.
+    #[serde(flatten)]
+    policy: T,
+
+    /// The action patterns of the policy that matched no known action.
+    unmatched: Vec<crate::expansion::UnmatchedPattern>,
+}
+
+/// Reports the expansion of the policy documents embedded in infrastructure definitions.
+///
+/// # Arguments
+///
+/// * `scanned` - The expanded policies.
+/// * `format` - The output format of the report.
+/// * `strict` - Whether patterns matching no known action make the report fail.
+///
+/// # Returns
+///
+/// - `Ok(())` if the report is output successfully
+/// - `Err(Box<dyn std::error::Error>)` if the policies cannot be serialized, or if `strict`
+///   is set and an action pattern matches no known action
+fn report_scanned_policies<T: EmbeddedPolicy>(
+    scanned: &[ScannedPolicy<T>],
+    format: OutputFormat,
+    strict: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let unmatched = scanned
+        .iter()
+        .map(|scanned| scanned.unmatched.len())
+        .sum::<usize>();
+
+    match format {
+        OutputFormat::Text => {
+            for scanned in scanned {
+                println!(
+                    "[+] {}: {}: {}",
+                    scanned.source,
+                    scanned.policy,
+                    serde_json::to_string_pretty(scanned.policy.document())?
+                );
+                scanned
+                    .unmatched
+                    .iter()
+                    .for_each(|pattern| eprintln!("\t[!] {}", pattern));
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(scanned)?),
+    }
+
+    if strict && unmatched > 0 {
+        return Err(format!("{} action pattern(s) matched no known actions", unmatched).into());
+    }
+    Ok(())
+}
+
+/// Loads a catalog from a local JSON file or directory in the AWS IAM actions format.
+///
+/// # Arguments
+///
+/// * `path` - The path to the catalog file or directory.
+///
+/// # Returns
+///
+/// - `Ok(Catalog)` with the loaded catalog
+/// - `Err(Box<dyn std::error::Error>)` if the catalog cannot be read or parsed
+fn load_local_catalog(path: &str) -> Result<crate::catalog::Catalog, Box<dyn std::error::Error>> {
+    crate::catalog::Catalog::from_source(&crate::source::LocalSource {
+        path: path.to_string(),
+    })
+}
+
+/// Prints the date of the embedded catalog snapshot and compares it with the cache.
+///
+/// The comparison reports which of the two is newer, and the actions present in only one of them.
+///
+/// # Returns
+///
+/// - `Ok(())` if the command executes successfully
+/// - `Err(Box<dyn std::error::Error>)` if the snapshot or the cache cannot be parsed
+fn snapshot_info() -> Result<(), Box<dyn std::error::Error>> {
+    let action_names =
+        |iam_actions_json: &str| -> Result<(usize, HashSet<String>), Box<dyn std::error::Error>> {
+            let services = serde_json::from_str::<Vec<crate::types::AwsService>>(iam_actions_json)?;
+            let actions = services
+                .iter()
+                .flat_map(|service| service.actions.iter())
+                .map(|action| action.name.clone())
+                .collect::<HashSet<String>>();
+            Ok((services.len(), actions))
+        };
+
+    let Some(snapshot) = crate::utils::embedded_snapshot() else {
+        println!(
+            "[!] This build does not embed a catalog snapshot (build with `--features embedded-catalog`)."
+        );
+        return Ok(());
+    };
+    let (snapshot_services, snapshot_actions) = action_names(snapshot.json)?;
+    println!(
+        "[*] Embedded catalog snapshot: {} ({} services, {} actions)",
+        snapshot.date,
+        snapshot_services,
+        snapshot_actions.len()
+    );
+
+    let Some((cache_date, cached_data)) = crate::utils::read_iam_actions_cache()? else {
+        println!("[!] No AWS IAM actions cache found to compare with.");
+        return Ok(());
+    };
+    let (cache_services, cache_actions) = action_names(&cached_data)?;
+    println!(
+        "[*] Cached catalog: {} ({} services, {} actions)",
+        cache_date,
+        cache_services,
+        cache_actions.len()
+    );
+
+    match (parse_date(&cache_date), parse_date(snapshot.date)) {
+        (Some(cache_date), Some(snapshot_date)) => match cache_date.cmp(&snapshot_date) {
+            std::cmp::Ordering::Greater => {
+                println!("[+] The cache is newer than the embedded snapshot.")
+            }
+            std::cmp::Ordering::Less => {
+                println!("[+] The embedded snapshot is newer than the cache.")
+            }
+            std::cmp::Ordering::Equal => {
+                println!("[+] The cache and the embedded snapshot have the same date.")
+            }
+        },
+        _ => println!(
+            "[!] Cannot compare the dates of the cache ({}) and the embedded snapshot ({}).",
+            cache_date, snapshot.date
+        ),
+    }
+    println!(
+        "[+] {} action(s) only in the cache, {} action(s) only in the embedded snapshot.",
+        cache_actions.difference(&snapshot_actions).count(),
+        snapshot_actions.difference(&cache_actions).count()
+    );
+
+    Ok(())
+}
+
+/// Parses a `YYYY-MM-DD` date into its year, month and day, which compare chronologically.
+///
+/// # Returns
+///
+/// The date, or `None` if it is not a valid `YYYY-MM-DD` date (e.g., "unknown").
+fn parse_date(date: &str) -> Option<(u32, u32, u32)> {
+    let [year, month, day] = date.split('-').collect::<Vec<&str>>()[..] else {
+        return None;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let date = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    ((1..=12).contains(&date.1) && (1..=31).contains(&date.2)).then_some(date)
+}
+
+/// Represents the top-level command-line arguments and options.
+///
+/// This struct is the entry point for argument parsing and contains the subcommand
+/// that the user wishes to execute.
+#[derive(Debug, clap::Parser)]
 pub struct Args {
-    /// The subcommand to execute (either "list-services" or "expand").
+    /// The source to load the catalog of AWS IAM services and actions from.
+    ///
+    /// Defaults to the `catalog_source` of the configuration file, or to the AWS IAM Actions API.
+    #[arg(long = "catalog-source", global = true, value_enum)]
+    catalog_source: Option<crate::source::CatalogSourceKind>,
+
+    /// The path of the catalog file or directory, for local catalog sources.
+    #[arg(long = "catalog-path", global = true)]
+    catalog_path: Option<String>,
+
+    /// Load the catalog from a local copy of the AWS Service Authorization Reference.
+    ///
+    /// Shorthand for `--catalog-source service-reference --catalog-path <PATH>`.
+    #[arg(
+        long = "service-reference",
+        global = true,
+        conflicts_with_all = ["catalog_source", "catalog_path"]
+    )]
+    service_reference: Option<String>,
+
+    /// Never reach the network: use the cache, or the catalog snapshot embedded with the
+    /// `embedded-catalog` feature.
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// The subcommand to execute (see [`Action`]).
     #[clap(subcommand)]
     action: Action,
 }
 
 impl Args {
+    /// Selects the catalog source from the command-line flags and the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Box<dyn CatalogSource>)` with the selected source
+    /// - `Err(Box<dyn std::error::Error>)` if the configuration file is invalid or a local
+    ///   source has no path
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use aws_iam_expansion::command::Args;
+    /// # use clap::Parser;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let args = Args::parse();
+    /// let services = args.catalog_source()?.load()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn catalog_source(
+        &self,
+    ) -> Result<Box<dyn crate::source::CatalogSource>, Box<dyn std::error::Error>> {
+        let mut config = crate::source::CatalogConfig::load()?;
+        config.offline |= self.offline;
+        match &self.service_reference {
+            Some(path) => crate::source::select_catalog_source(
+                Some(crate::source::CatalogSourceKind::ServiceReference),
+                Some(path),
+                &config,
+            ),
+            None => crate::source::select_catalog_source(
+                self.catalog_source,
+                self.catalog_path.as_deref(),
+                &config,
+            ),
+        }
+    }
+
+    /// Loads the catalog, if the subcommand needs it, and executes the parsed command-line
+    /// arguments.
+    ///
+    /// The catalog is loaded from the selected catalog source (see
+    /// [`catalog_source`](Self::catalog_source)), except for the `delete-cache`,
+    /// `update-cache` and `snapshot-info` subcommands, which do not use it and must work when
+    /// it cannot be loaded (e.g., offline without a cache).
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command executes successfully
+    /// - `Err(Box<dyn std::error::Error>)` if the catalog cannot be loaded or an error occurs
+    ///   during execution
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use aws_iam_expansion::command::Args;
+    /// # use clap::Parser;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Args::parse().run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let catalog = match self.action {
+            Action::DeleteCache | Action::UpdateCache | Action::SnapshotInfo => {
+                crate::catalog::Catalog::new(std::iter::empty())
+            }
+            _ => crate::catalog::Catalog::from_source(self.catalog_source()?.as_ref())?,
+        };
+        self.handle(&catalog)
+    }
+
     /// Processes and executes the parsed command-line arguments.
     ///
     /// This function dispatches to the appropriate handler based on the subcommand specified
@@ -322,8 +1837,7 @@ impl Args {
     ///
     /// # Arguments
     ///
-    /// * `available_services_permissions` - A HashMap mapping service prefixes to their associated
-    ///   service and action data from the AWS IAM actions JSON.
+    /// * `catalog` - The catalog of known AWS IAM services and actions.
     ///
     /// # Returns
     ///
@@ -333,35 +1847,353 @@ impl Args {
     /// # Examples
     ///
     /// ```no_run
+    /// # use aws_iam_expansion::{catalog::Catalog, command::Args};
+    /// # use clap::Parser;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let args = Args::parse();
-    /// args.handle(available_services_permissions)?;
+    /// let catalog = Catalog::from_source(args.catalog_source()?.as_ref())?;
+    /// args.handle(&catalog)?;
+    /// # Ok(())
+    /// # }
     /// ```
     pub fn handle(
         &self,
-        available_services_permissions: HashMap<String, Vec<crate::types::AwsService>>,
+        catalog: &crate::catalog::Catalog,
     ) -> Result<(), Box<dyn std::error::Error>> {
         match &self.action {
-            Action::ListServices => {
-                println!("[*] Listing AWS IAM services");
-                available_services_permissions
-                    .keys()
-                    .into_iter()
-                    .for_each(|service_prefix| println!("\t[+] {}", service_prefix));
-
-                Ok(())
-            }
-            Action::Expand(expand_sub_cmd) => expand_sub_cmd.handle(available_services_permissions),
-            Action::ExpandFile(expand_file_sub_cmd) => {
-                expand_file_sub_cmd.handle(available_services_permissions)
+            Action::ListServices(list_services_sub_cmd) => list_services_sub_cmd.handle(catalog),
+            Action::Expand(expand_sub_cmd) => expand_sub_cmd.handle(catalog),
+            Action::ExpandFile(expand_file_sub_cmd) => expand_file_sub_cmd.handle(catalog),
+            Action::DescribeAction(describe_action_sub_cmd) => {
+                describe_action_sub_cmd.handle(catalog)
             }
+            Action::WhoGrants(who_grants_sub_cmd) => who_grants_sub_cmd.handle(catalog),
+            Action::Compress(compress_sub_cmd) => compress_sub_cmd.handle(catalog),
+            Action::CatalogDiff(catalog_diff_sub_cmd) => catalog_diff_sub_cmd.handle(catalog),
+            Action::Drift(drift_sub_cmd) => drift_sub_cmd.handle(catalog),
+            Action::ScanTemplate(scan_template_sub_cmd) => scan_template_sub_cmd.handle(catalog),
+            Action::ScanPlan(scan_plan_sub_cmd) => scan_plan_sub_cmd.handle(catalog),
             Action::DeleteCache => {
                 crate::utils::delete_iam_actions_cache()?;
                 Ok(())
             }
-            Action::UpdateCache => {
-                crate::utils::update_iam_actions_cache()?;
-                Ok(())
-            }
+            Action::UpdateCache => self.catalog_source()?.update_cache(),
+            Action::SnapshotInfo => snapshot_info(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// Writes a file in a directory of the test, returning its path.
+    fn write_file(test: &str, name: &str, contents: &str) -> String {
+        let dir =
+            std::env::temp_dir().join(format!("aws_iam_expansion-{}-{}", test, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    /// Runs the command line against the fixture catalog.
+    fn run(args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+        let args = std::iter::once("aws-iam-expansion").chain(args.iter().copied());
+        Args::try_parse_from(args)?.handle(&crate::test_fixtures::fixture_catalog())
+    }
+
+    /// Runs expand-file on a policy, returning the statements of the expanded policy.
+    fn expand_file(
+        test: &str,
+        policy: serde_json::Value,
+        args: &[&str],
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let policy_file = write_file(test, "policy.json", &policy.to_string());
+        let output_file = write_file(test, "expanded.json", "");
+        let mut command = vec![
+            "expand-file",
+            "--policy-file",
+            &policy_file,
+            "--output-file",
+            &output_file,
+        ];
+        command.extend(args);
+        run(&command)?;
+        let expanded = std::fs::read_to_string(&output_file)?;
+        Ok(serde_json::from_str::<serde_json::Value>(&expanded)?["Statement"].clone())
+    }
+
+    #[test]
+    fn expand_file_filters_allow_actions_only() {
+        let statements = expand_file(
+            "filter",
+            serde_json::json!({ "Version": "2012-10-17", "Statement": [
+                { "Effect": "Allow", "Action": "iam:*", "Resource": "*" },
+                { "Effect": "Allow", "NotAction": ["iam:*"], "Resource": "*" },
+                { "Effect": "Deny", "Action": "s3:*", "Resource": "*" }
+            ] }),
+            &["--access-level", "read"],
+        )
+        .unwrap();
+
+        assert_eq!(statements[0]["Action"], serde_json::json!(["iam:GetRole"]));
+        assert_eq!(
+            statements[1]["NotAction"],
+            serde_json::json!([
+                "iam:AttachRolePolicy",
+                "iam:CreateRole",
+                "iam:DeleteRole",
+                "iam:GetRole",
+                "iam:ListRoles",
+                "iam:PassRole",
+                "iam:TagRole"
+            ])
+        );
+        assert_eq!(statements[2]["Action"].as_array().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn expand_file_drops_statements_emptied_by_the_filter() {
+        let statements = expand_file(
+            "emptied",
+            serde_json::json!({ "Statement": [
+                { "Sid": "Create", "Effect": "Allow", "Action": "iam:Create*", "Resource": "*" },
+                { "Sid": "Read", "Effect": "Allow", "Action": "s3:Get*", "Resource": "*" }
+            ] }),
+            &["--access-level", "read"],
+        )
+        .unwrap();
+        assert_eq!(statements.as_array().unwrap().len(), 1);
+        assert_eq!(statements[0]["Sid"], "Read");
+
+        let error = expand_file(
+            "emptied-all",
+            serde_json::json!({ "Statement": { "Effect": "Allow", "Action": "iam:Create*", "Resource": "*" } }),
+            &["--access-level", "read"],
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("grants any action"));
+    }
+
+    #[test]
+    fn expand_file_complements_allow_not_actions() {
+        let statements = expand_file(
+            "complement",
+            serde_json::json!({ "Statement": [
+                { "Effect": "Allow", "NotAction": "iam:*", "Resource": "*" },
+                { "Effect": "Deny", "NotAction": "s3:Get*", "Resource": "*" }
+            ] }),
+            &["--not-action-mode", "complement"],
+        )
+        .unwrap();
+
+        let actions = statements[0]["Action"].as_array().unwrap();
+        assert_eq!(actions.len(), 8);
+        assert!(
+            actions
+                .iter()
+                .all(|action| !action.as_str().unwrap().starts_with("iam:"))
+        );
+        assert!(statements[0].get("NotAction").is_none());
+        assert_eq!(
+            statements[1]["NotAction"],
+            serde_json::json!(["s3:GetObject"])
+        );
+    }
+
+    #[test]
+    fn expand_file_checks_complement_services() {
+        let policy = serde_json::json!({ "Statement": { "Effect": "Allow", "NotAction": "iam:Get*", "Resource": "*" } });
+
+        let statements = expand_file(
+            "complement-services",
+            policy.clone(),
+            &[
+                "--not-action-mode",
+                "complement",
+                "--complement-services",
+                "iam",
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            statements["Action"],
+            serde_json::json!([
+                "iam:AttachRolePolicy",
+                "iam:CreateRole",
+                "iam:DeleteRole",
+                "iam:ListRoles",
+                "iam:PassRole",
+                "iam:TagRole"
+            ])
+        );
+
+        assert!(
+            expand_file(
+                "complement-services-mode",
+                policy,
+                &["--complement-services", "iam"]
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn compress_rewrites_allow_actions_only() {
+        let policy_file = write_file(
+            "compress",
+            "policy.json",
+            &serde_json::json!({ "Statement": [
+                { "Effect": "Allow", "Action": ["s3:PutBucketPolicy", "s3:PutObject", "s3:PutObjectTagging"], "Resource": "*" },
+                { "Effect": "Allow", "NotAction": ["s3:PutBucketPolicy", "s3:PutObject", "s3:PutObjectTagging"], "Resource": "*" },
+                { "Effect": "Deny", "Action": ["s3:GetObject", "s3:PutObject"], "Resource": "*" }
+            ] })
+            .to_string(),
+        );
+        let output_file = write_file("compress", "compressed.json", "");
+        run(&[
+            "compress",
+            "--policy-file",
+            &policy_file,
+            "--output-file",
+            &output_file,
+        ])
+        .unwrap();
+
+        let compressed = serde_json::from_str::<serde_json::Value>(
+            &std::fs::read_to_string(&output_file).unwrap(),
+        )
+        .unwrap();
+        let statements = &compressed["Statement"];
+        assert_eq!(statements[0]["Action"], serde_json::json!(["s3:Put*"]));
+        assert_eq!(
+            statements[1]["NotAction"],
+            serde_json::json!(["s3:PutBucketPolicy", "s3:PutObject", "s3:PutObjectTagging"])
+        );
+        assert_eq!(
+            statements[2]["Action"],
+            serde_json::json!(["s3:GetObject", "s3:PutObject"])
+        );
+    }
+
+    #[test]
+    fn parses_snapshot_dates() {
+        assert_eq!(parse_date("2026-10-01"), Some((2026, 10, 1)));
+        assert!(parse_date("2026-09-30") < parse_date("2026-10-01"));
+        assert_eq!(parse_date("unknown"), None);
+        assert_eq!(parse_date("2026-13-01"), None);
+        assert_eq!(parse_date("2026-1-01"), None);
+    }
+
+    #[test]
+    fn drift_fails_on_new_allowed_actions_only() {
+        let old = write_file(
+            "drift",
+            "old.json",
+            &serde_json::json!([{
+                "service": "AWS Identity and Access Management (IAM)",
+                "servicePrefix": "iam",
+                "actions": [{ "action": "iam:GetRole", "type": "Read" }]
+            }])
+            .to_string(),
+        );
+        let deny = write_file(
+            "drift",
+            "deny.json",
+            r#"{ "Statement": { "Effect": "Deny", "Action": "iam:*", "Resource": "*" } }"#,
+        );
+        let allow = write_file(
+            "drift",
+            "allow.json",
+            r#"{ "Statement": { "Effect": "Allow", "Action": "iam:*", "Resource": "*" } }"#,
+        );
+
+        assert!(
+            run(&[
+                "drift",
+                "--policy-file",
+                &deny,
+                "--old",
+                &old,
+                "--fail-on-drift"
+            ])
+            .is_ok()
+        );
+        assert!(
+            run(&[
+                "drift",
+                "--policy-file",
+                &allow,
+                "--old",
+                &old,
+                "--fail-on-drift"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn scan_template_resolves_conditional_lists() {
+        let template = write_file(
+            "scan-template",
+            "stack.yaml",
+            r#"
+Resources:
+  AppRole:
+    Type: AWS::IAM::Role
+    Properties:
+      AssumeRolePolicyDocument:
+        Statement:
+          - Effect: Allow
+            Principal: { Service: lambda.amazonaws.com }
+            Action: iam:PassRole
+      Policies:
+        - PolicyName: Artifacts
+          PolicyDocument:
+            Statement:
+              - Effect: Allow
+                Action: !If [IsProd, [s3:GetObject], [s3:GetObject, s3:PutObject]]
+                Resource: !Sub arn:aws:s3:::${Bucket}/*
+"#,
+        );
+        assert!(run(&["scan-template", "--template", &template, "--strict"]).is_ok());
+
+        let typo = write_file(
+            "scan-template",
+            "typo.json",
+            r#"{ "Resources": { "Policy": { "Type": "AWS::IAM::ManagedPolicy", "Properties": {
+                "PolicyDocument": { "Statement": [{ "Effect": "Allow", "Action": "s3:GetObjekt", "Resource": "*" }] }
+            } } } }"#,
+        );
+        assert!(run(&["scan-template", "--template", &typo]).is_ok());
+        assert!(run(&["scan-template", "--template", &typo, "--strict"]).is_err());
+    }
+
+    #[test]
+    fn scan_plan_checks_planned_policies() {
+        let plan = |action: &str| {
+            serde_json::json!({
+                "planned_values": { "root_module": { "resources": [{
+                    "address": "aws_iam_policy.app",
+                    "type": "aws_iam_policy",
+                    "values": { "policy": serde_json::json!({ "Statement": [
+                        { "Effect": "Allow", "Action": action, "Resource": "*" }
+                    ] }).to_string() }
+                }] } },
+                "resource_changes": [{
+                    "address": "aws_iam_policy.app",
+                    "type": "aws_iam_policy",
+                    "change": { "actions": ["create"], "after_unknown": {} }
+                }]
+            })
+            .to_string()
+        };
+
+        let known = write_file("scan-plan", "known.json", &plan("s3:Get*"));
+        let typo = write_file("scan-plan", "typo.json", &plan("s3:Gett*"));
+        assert!(run(&["scan-plan", "--plan", &known, "--strict"]).is_ok());
+        assert!(run(&["scan-plan", "--plan", &typo, "--strict"]).is_err());
+    }
+}
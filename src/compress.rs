@@ -0,0 +1,222 @@
+//! Compression of concrete action lists into minimal sets of safe wildcards.
+//!
+//! This is the inverse of the expansion: given a set of concrete actions, it produces the
+//! smallest set of `service:Prefix*` patterns that matches exactly those actions and
+//! nothing else in the current catalog. Expanded policies regularly exceed IAM size
+//! limits, while hand-written wildcards tend to over-grant; compressed patterns are as
+//! short as the catalog allows without granting anything extra.
+
+use std::collections::{BTreeMap, HashSet};
+
+/// Compresses a list of actions into the smallest equivalent set of wildcard patterns.
+///
+/// For every service, the catalog actions are walked as a trie of case-folded names. The
+/// largest subtrees whose actions are all wanted are replaced with a single pattern made
+/// of the longest prefix they share followed by `*`; a subtree with a single action is
+/// written as that exact action. Since every emitted pattern only covers wanted actions,
+/// the result matches exactly the given actions and nothing else in the catalog.
+///
+/// Entries containing wildcards are expanded first. Entries that match no known action
+/// are kept verbatim, at the end of the result.
+///
+/// # Arguments
+///
+/// * `actions` - The actions (or patterns) to compress.
+/// * `index` - The index of every known IAM action.
+///
+/// # Returns
+///
+/// The compressed patterns, sorted by service and in trie order within each service.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::{compress::compress_actions, pattern::ActionIndex};
+///
+/// let index = ActionIndex::new([
+///     "iam:CreatePolicy",
+///     "iam:CreateRole",
+///     "iam:CreateUser",
+///     "iam:DeleteRole",
+///     "iam:GetRole",
+///     "s3:GetObject",
+/// ]);
+///
+/// let compressed = compress_actions(
+///     &[
+///         "iam:CreatePolicy".to_string(),
+///         "iam:CreateRole".to_string(),
+///         "iam:CreateUser".to_string(),
+///         "iam:GetRole".to_string(),
+///         "s3:GetObject".to_string(),
+///     ],
+///     &index,
+/// );
+/// assert_eq!(compressed, vec!["iam:Create*", "iam:GetRole", "s3:GetObject"]);
+/// ```
+pub fn compress_actions(actions: &[String], index: &crate::pattern::ActionIndex) -> Vec<String> {
+    let mut wanted: HashSet<String> = HashSet::new();
+    let mut unknown: Vec<String> = Vec::new();
+
+    for action in actions {
+        let matched = index.expand(action);
+        if matched.is_empty() {
+            if !unknown.contains(action) {
+                unknown.push(action.clone());
+            }
+            continue;
+        }
+        wanted.extend(matched.iter().map(|action| action.to_ascii_lowercase()));
+    }
+
+    let catalog = index.expand("*");
+    let mut compressed = if !wanted.is_empty() && wanted.len() == catalog.len() {
+        vec!["*".to_string()]
+    } else {
+        // Group the catalog actions of every service that has at least one wanted action.
+        let mut services: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+        for action in catalog {
+            let folded = action.to_ascii_lowercase();
+            let Some((service, _)) = folded.split_once(':') else {
+                continue;
+            };
+            services
+                .entry(service.to_string())
+                .or_default()
+                .push((folded.clone(), action));
+        }
+        services.retain(|_, members| members.iter().any(|(folded, _)| wanted.contains(folded)));
+
+        let mut compressed: Vec<String> = Vec::new();
+        for (service, mut members) in services {
+            members.sort();
+            compress_subtree(service.len() + 1, &members, &wanted, &mut compressed);
+        }
+        compressed
+    };
+
+    compressed.extend(unknown);
+    compressed
+}
+
+/// Compresses the catalog actions sharing a prefix of the given length.
+///
+/// # Arguments
+///
+/// * `depth` - The length of the prefix shared by every member.
+/// * `members` - The (case-folded, canonical) names of the catalog actions sharing the
+///   prefix, sorted by case-folded name.
+/// * `wanted` - The case-folded names of the actions to match.
+/// * `compressed` - The list the resulting patterns are appended to.
+fn compress_subtree(
+    depth: usize,
+    members: &[(String, String)],
+    wanted: &HashSet<String>,
+    compressed: &mut Vec<String>,
+) {
+    if members.iter().all(|(folded, _)| wanted.contains(folded)) {
+        if let [(_, canonical)] = members {
+            compressed.push(canonical.clone());
+        } else {
+            let first = &members[0].0;
+            let last = &members[members.len() - 1].0;
+            // Members are sorted, so the first and last share the longest common prefix.
+            let shared = first
+                .bytes()
+                .zip(last.bytes())
+                .take_while(|(a, b)| a == b)
+                .count();
+            compressed.push(format!("{}*", &members[0].1[..shared]));
+        }
+        return;
+    }
+
+    let mut start = 0;
+    while start < members.len() {
+        let (folded, canonical) = &members[start];
+        if folded.len() == depth {
+            // The member is the prefix itself, which only an exact entry can match.
+            if wanted.contains(folded) {
+                compressed.push(canonical.clone());
+            }
+            start += 1;
+            continue;
+        }
+
+        let next = folded.as_bytes()[depth];
+        let end = start
+            + members[start..]
+                .iter()
+                .take_while(|(folded, _)| folded.len() > depth && folded.as_bytes()[depth] == next)
+                .count();
+        if members[start..end]
+            .iter()
+            .any(|(folded, _)| wanted.contains(folded))
+        {
+            compress_subtree(depth + 1, &members[start..end], wanted, compressed);
+        }
+        start = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The index the compression is tested against.
+    fn index() -> crate::pattern::ActionIndex {
+        crate::pattern::ActionIndex::new([
+            "iam:Get",
+            "iam:GetRole",
+            "iam:GetRolePolicy",
+            "iam:PassRole",
+            "s3:GetObject",
+        ])
+    }
+
+    /// Compresses the given actions against [`index`].
+    fn compress(actions: &[&str]) -> Vec<String> {
+        let actions = actions
+            .iter()
+            .map(|action| action.to_string())
+            .collect::<Vec<String>>();
+        compress_actions(&actions, &index())
+    }
+
+    #[test]
+    fn writes_an_action_equal_to_the_prefix_as_is() {
+        let members = ["iam:Get", "iam:GetRole", "iam:GetRolePolicy"]
+            .map(|action| (action.to_ascii_lowercase(), action.to_string()));
+        let wanted = HashSet::from(["iam:get".to_string(), "iam:getrolepolicy".to_string()]);
+
+        let mut compressed = Vec::new();
+        compress_subtree("iam:Get".len(), &members, &wanted, &mut compressed);
+        assert_eq!(compressed, vec!["iam:Get", "iam:GetRolePolicy"]);
+
+        assert_eq!(compress(&["iam:Get"]), vec!["iam:Get"]);
+        assert_eq!(compress(&["iam:GetRole*"]), vec!["iam:GetRole*"]);
+        assert_eq!(compress(&["iam:Get*"]), vec!["iam:Get*"]);
+    }
+
+    #[test]
+    fn compresses_the_whole_catalog_into_a_single_wildcard() {
+        assert_eq!(compress(&["iam:*", "s3:GetObject"]), vec!["*"]);
+        assert_eq!(compress(&["iam:*"]), vec!["iam:*"]);
+    }
+
+    #[test]
+    fn compresses_mixed_case_actions_with_canonical_casing() {
+        assert_eq!(
+            compress(&["IAM:getrole", "iam:GETROLEPOLICY", "S3:getObject"]),
+            vec!["iam:GetRole*", "s3:GetObject"]
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_entries_verbatim_at_the_end() {
+        assert_eq!(
+            compress(&["iam:Typo", "iam:PassRole", "ec2:Run*", "iam:Typo"]),
+            vec!["iam:PassRole", "iam:Typo", "ec2:Run*"]
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Comparison of two catalogs of AWS IAM services and actions.
+//!
+//! AWS adds actions every week, and occasionally removes or reclassifies some. This module
+//! compares two catalogs, typically an older snapshot and the current cache, and reports
+//! the services and actions that were added or removed, and the actions whose access level
+//! changed, as a changelog that can be printed or serialized to JSON.
+
+/// An action reported by a catalog diff, with its access level.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DiffedAction {
+    /// The name of the action (e.g., "s3:GetObject").
+    pub action: String,
+
+    /// The access level of the action (e.g., "Read").
+    pub access_level: String,
+}
+
+/// An action whose access level differs between two catalogs.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AccessLevelChange {
+    /// The name of the action, as written in the new catalog.
+    pub action: String,
+
+    /// The access level in the old catalog.
+    pub old: String,
+
+    /// The access level in the new catalog.
+    pub new: String,
+}
+
+/// The changes to the actions of a single service.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ServiceDiff {
+    /// The service prefix (e.g., "s3").
+    pub service: String,
+
+    /// The actions only present in the new catalog.
+    pub added_actions: Vec<DiffedAction>,
+
+    /// The actions only present in the old catalog.
+    pub removed_actions: Vec<DiffedAction>,
+
+    /// The actions whose access level changed.
+    pub access_level_changes: Vec<AccessLevelChange>,
+}
+
+/// The differences between two catalogs.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CatalogDiff {
+    /// The prefixes of the services only present in the new catalog.
+    pub added_services: Vec<String>,
+
+    /// The prefixes of the services only present in the old catalog.
+    pub removed_services: Vec<String>,
+
+    /// The changes to the actions of every service with at least one change, sorted by prefix.
+    ///
+    /// The actions of added and removed services are reported as added and removed actions.
+    pub services: Vec<ServiceDiff>,
+}
+
+impl CatalogDiff {
+    /// Returns whether the two catalogs hold the same services and actions.
+    pub fn is_empty(&self) -> bool {
+        self.added_services.is_empty()
+            && self.removed_services.is_empty()
+            && self.services.is_empty()
+    }
+}
+
+impl std::fmt::Display for CatalogDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = |actions: fn(&ServiceDiff) -> usize| self.services.iter().map(actions).sum();
+        let added: usize = count(|service| service.added_actions.len());
+        let removed: usize = count(|service| service.removed_actions.len());
+        let changed: usize = count(|service| service.access_level_changes.len());
+        writeln!(
+            f,
+            "[*] {} service(s) added, {} removed; {} action(s) added, {} removed, {} access level change(s)",
+            self.added_services.len(),
+            self.removed_services.len(),
+            added,
+            removed,
+            changed
+        )?;
+
+        if !self.added_services.is_empty() {
+            writeln!(f, "[+] Added services: {}", self.added_services.join(", "))?;
+        }
+        if !self.removed_services.is_empty() {
+            writeln!(
+                f,
+                "[-] Removed services: {}",
+                self.removed_services.join(", ")
+            )?;
+        }
+        for service in &self.services {
+            writeln!(f, "[*] {}:", service.service)?;
+            for action in &service.added_actions {
+                writeln!(f, "\t[+] {} ({})", action.action, action.access_level)?;
+            }
+            for action in &service.removed_actions {
+                writeln!(f, "\t[-] {} ({})", action.action, action.access_level)?;
+            }
+            for change in &service.access_level_changes {
+                writeln!(
+                    f,
+                    "\t[!] {}: {} -> {}",
+                    change.action, change.old, change.new
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares two catalogs.
+///
+/// Services and actions are matched case-insensitively, and access levels are compared
+/// after normalization (see [`normalize_access_level`](crate::expansion::normalize_access_level)).
+///
+/// # Arguments
+///
+/// * `old` - The older catalog.
+/// * `new` - The newer catalog.
+///
+/// # Returns
+///
+/// A `CatalogDiff` with the added and removed services, and the per-service action changes.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::{catalog::Catalog, diff::diff_catalogs, types::AwsService};
+///
+/// let old = Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([{
+///     "service": "Amazon S3",
+///     "servicePrefix": "s3",
+///     "actions": [
+///         { "action": "s3:GetObject", "type": "Read" },
+///         { "action": "s3:GetBucketTagging", "type": "Read" }
+///     ]
+/// }])).unwrap());
+/// let new = Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([
+///     {
+///         "service": "Amazon S3",
+///         "servicePrefix": "s3",
+///         "actions": [
+///             { "action": "s3:GetObject", "type": "Read" },
+///             { "action": "s3:GetBucketTagging", "type": "Tagging" },
+///             { "action": "s3:PutObject", "type": "Write" }
+///         ]
+///     },
+///     {
+///         "service": "Amazon S3 Vectors",
+///         "servicePrefix": "s3vectors",
+///         "actions": [{ "action": "s3vectors:QueryVectors", "type": "Read" }]
+///     }
+/// ])).unwrap());
+///
+/// let diff = diff_catalogs(&old, &new);
+/// assert_eq!(diff.added_services, vec!["s3vectors"]);
+/// assert_eq!(diff.services[0].added_actions[0].action, "s3:PutObject");
+/// assert_eq!(diff.services[0].access_level_changes[0].new, "Tagging");
+/// assert_eq!(diff.services[1].added_actions[0].action, "s3vectors:QueryVectors");
+/// ```
+pub fn diff_catalogs(old: &crate::catalog::Catalog, new: &crate::catalog::Catalog) -> CatalogDiff {
+    let mut prefixes = old
+        .services()
+        .chain(new.services())
+        .map(|service| service.prefix.to_lowercase())
+        .collect::<Vec<String>>();
+    prefixes.sort();
+    prefixes.dedup();
+
+    let mut diff = CatalogDiff {
+        added_services: Vec::new(),
+        removed_services: Vec::new(),
+        services: Vec::new(),
+    };
+    for prefix in prefixes {
+        let old_service = old.service(&prefix);
+        let new_service = new.service(&prefix);
+        let Some(service) = new_service.or(old_service) else {
+            continue;
+        };
+        match (old_service, new_service) {
+            (None, Some(_)) => diff.added_services.push(service.prefix.clone()),
+            (Some(_), None) => diff.removed_services.push(service.prefix.clone()),
+            _ => {}
+        }
+
+        let mut service_diff = ServiceDiff {
+            service: service.prefix.clone(),
+            added_actions: Vec::new(),
+            removed_actions: Vec::new(),
+            access_level_changes: Vec::new(),
+        };
+        let old_actions = actions_by_name(old_service);
+        let new_actions = actions_by_name(new_service);
+        for action in new_service
+            .iter()
+            .flat_map(|service| service.actions.iter())
+        {
+            match old_actions.get(&action.name.to_lowercase()) {
+                None => service_diff.added_actions.push(DiffedAction {
+                    action: action.name.clone(),
+                    access_level: action.action_type.clone(),
+                }),
+                Some(previous)
+                    if crate::expansion::normalize_access_level(&previous.action_type)
+                        != crate::expansion::normalize_access_level(&action.action_type) =>
+                {
+                    service_diff.access_level_changes.push(AccessLevelChange {
+                        action: action.name.clone(),
+                        old: previous.action_type.clone(),
+                        new: action.action_type.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for action in old_service
+            .iter()
+            .flat_map(|service| service.actions.iter())
+        {
+            if !new_actions.contains_key(&action.name.to_lowercase()) {
+                service_diff.removed_actions.push(DiffedAction {
+                    action: action.name.clone(),
+                    access_level: action.action_type.clone(),
+                });
+            }
+        }
+
+        service_diff
+            .added_actions
+            .sort_by_key(|action| action.action.to_lowercase());
+        service_diff
+            .removed_actions
+            .sort_by_key(|action| action.action.to_lowercase());
+        service_diff
+            .access_level_changes
+            .sort_by_key(|change| change.action.to_lowercase());
+        if !service_diff.added_actions.is_empty()
+            || !service_diff.removed_actions.is_empty()
+            || !service_diff.access_level_changes.is_empty()
+        {
+            diff.services.push(service_diff);
+        }
+    }
+    diff
+}
+
+/// Indexes the actions of a service by their lowercased name.
+///
+/// Actions are looked up within the service being compared rather than across the whole
+/// catalog, as the same action name can be listed under several service prefixes.
+///
+/// # Arguments
+///
+/// * `service` - The service, if present in the catalog.
+fn actions_by_name(
+    service: Option<&crate::types::AwsService>,
+) -> std::collections::HashMap<String, &crate::types::AwsAction> {
+    service
+        .iter()
+        .flat_map(|service| service.actions.iter())
+        .map(|action| (action.name.to_lowercase(), action))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::catalog;
+
+    #[test]
+    fn compares_actions_within_their_service() {
+        let old = catalog(serde_json::json!([
+            {
+                "service": "Amazon S3",
+                "servicePrefix": "s3",
+                "actions": [
+                    { "action": "s3:GetObject", "type": "Read" },
+                    { "action": "s3:ListBucket", "type": "List" }
+                ]
+            },
+            {
+                "service": "Amazon S3 Object Lambda",
+                "servicePrefix": "s3-object-lambda",
+                "actions": [{ "action": "s3:ListBucket", "type": "List" }]
+            }
+        ]));
+        let new = catalog(serde_json::json!([
+            {
+                "service": "Amazon S3",
+                "servicePrefix": "s3",
+                "actions": [
+                    { "action": "s3:GetObject", "type": "Read" },
+                    { "action": "s3:ListBucket", "type": "List" }
+                ]
+            },
+            {
+                "service": "Amazon S3 Object Lambda",
+                "servicePrefix": "s3-object-lambda",
+                "actions": [{ "action": "s3:GetObject", "type": "Read" }]
+            }
+        ]));
+
+        let diff = diff_catalogs(&old, &new);
+        assert_eq!(diff.services.len(), 1);
+        assert_eq!(diff.services[0].service, "s3-object-lambda");
+        assert_eq!(diff.services[0].added_actions[0].action, "s3:GetObject");
+        assert_eq!(diff.services[0].removed_actions[0].action, "s3:ListBucket");
+    }
+}
@@ -0,0 +1,336 @@
+//! Wildcard drift detection across catalog versions.
+//!
+//! A wildcard such as `iam:*` or `iam:Create*` silently starts granting every action AWS
+//! adds to the catalog that it matches. This module compares what the patterns of a policy
+//! grant under an older and a newer catalog, and reports the concrete actions each of them
+//! grants only under the newer one, grouped by statement and by access level. The new
+//! actions covered by `Deny` statements are reported as newly denied, since they only make
+//! the policy stricter.
+
+use std::collections::HashSet;
+
+/// The newly granted actions of a single access level.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AccessLevelActions {
+    /// The access level of the actions (e.g., "Permissions management").
+    pub access_level: String,
+
+    /// The newly granted actions, in trie order.
+    pub actions: Vec<String>,
+}
+
+/// The actions a policy element grants under the newer catalog only.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PatternDrift {
+    /// The policy element ("Action" or "NotAction").
+    pub field: String,
+
+    /// The patterns responsible for the drift.
+    ///
+    /// For an `Action` element this is the drifting wildcard pattern. For a `NotAction`
+    /// element, which covers every action it does not exclude, these are the excluded
+    /// patterns, none of which match the new actions.
+    pub patterns: Vec<String>,
+
+    /// The newly granted actions, grouped by access level in the order Read, List, Write,
+    /// Tagging, Permissions management.
+    pub access_levels: Vec<AccessLevelActions>,
+}
+
+impl PatternDrift {
+    /// Returns the number of newly granted actions.
+    pub fn count(&self) -> usize {
+        self.access_levels
+            .iter()
+            .map(|access_level| access_level.actions.len())
+            .sum()
+    }
+}
+
+/// A policy statement whose patterns grant new actions under the newer catalog.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StatementDrift {
+    /// The policy file containing the statement.
+    pub policy_file: String,
+
+    /// The zero-based index of the statement in the policy.
+    pub statement_index: usize,
+
+    /// The identifier of the statement, if any.
+    pub sid: Option<String>,
+
+    /// The effect of the statement ("Allow" or "Deny").
+    pub effect: String,
+
+    /// The drifting patterns of the statement, in policy order.
+    pub patterns: Vec<PatternDrift>,
+}
+
+impl StatementDrift {
+    /// Returns `true` if the statement newly grants actions, `false` if it newly denies them.
+    pub fn grants(&self) -> bool {
+        self.effect == "Allow"
+    }
+}
+
+impl std::fmt::Display for StatementDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = crate::expansion::statement_label(self.statement_index, self.sid.as_deref());
+        if self.grants() {
+            writeln!(f, "[!] {} {} {}:", self.policy_file, label, self.effect)?;
+        } else {
+            writeln!(
+                f,
+                "[*] {} {} {} (newly denied):",
+                self.policy_file, label, self.effect
+            )?;
+        }
+        for pattern in &self.patterns {
+            let patterns = pattern
+                .patterns
+                .iter()
+                .map(|pattern| format!("'{}'", pattern))
+                .collect::<Vec<String>>()
+                .join(", ");
+            if pattern.field == "NotAction" {
+                writeln!(
+                    f,
+                    "\t[+] NotAction (not excluded by {}) now also covers {} action(s):",
+                    patterns,
+                    pattern.count()
+                )?;
+            } else {
+                writeln!(
+                    f,
+                    "\t[+] Action {} now also covers {} action(s):",
+                    patterns,
+                    pattern.count()
+                )?;
+            }
+            for access_level in &pattern.access_levels {
+                writeln!(
+                    f,
+                    "\t\t[-] {}: {}",
+                    access_level.access_level,
+                    access_level.actions.join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Groups actions by access level, using the access levels of a catalog.
+fn group_by_access_level(
+    actions: Vec<String>,
+    catalog: &crate::catalog::Catalog,
+) -> Vec<AccessLevelActions> {
+    let mut groups: Vec<AccessLevelActions> = Vec::new();
+    for action in actions {
+        let access_level = catalog.access_level(&action).unwrap_or("Unknown");
+        match groups
+            .iter_mut()
+            .find(|group| group.access_level == access_level)
+        {
+            Some(group) => group.actions.push(action),
+            None => groups.push(AccessLevelActions {
+                access_level: access_level.to_string(),
+                actions: vec![action],
+            }),
+        }
+    }
+    groups.sort_by_key(|group| crate::expansion::access_level_order(&group.access_level));
+    groups
+}
+
+/// Finds the statements of a policy granting actions under a newer catalog that they did
+/// not grant under an older one.
+///
+/// Every wildcard pattern of an `Action` element is expanded against both catalogs, and
+/// the actions only matched under the newer one are reported. A `NotAction` element is
+/// reported with the actions added to the catalog that none of its patterns exclude.
+/// Patterns without wildcards cannot drift and are ignored. `Deny` statements are reported
+/// too, with the actions they newly deny (see [`StatementDrift::grants`]).
+///
+/// # Arguments
+///
+/// * `policy_file` - The name of the policy file, used to identify the statements.
+/// * `policy` - The parsed policy document.
+/// * `old` - The older catalog.
+/// * `new` - The newer catalog.
+///
+/// # Returns
+///
+/// One `StatementDrift` per statement with at least one drifting pattern, in policy order.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::{
+///     catalog::Catalog, drift::detect_drift, types::{AWSPolicyDocument, AwsService},
+/// };
+///
+/// let catalog = |actions: serde_json::Value| {
+///     Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([{
+///         "service": "AWS Identity and Access Management (IAM)",
+///         "servicePrefix": "iam",
+///         "actions": actions
+///     }])).unwrap())
+/// };
+/// let old = catalog(serde_json::json!([{ "action": "iam:GetRole", "type": "Read" }]));
+/// let new = catalog(serde_json::json!([
+///     { "action": "iam:GetRole", "type": "Read" },
+///     { "action": "iam:SomethingDangerous", "type": "Permissions management" }
+/// ]));
+///
+/// let policy = serde_json::from_value::<AWSPolicyDocument>(serde_json::json!({
+///     "Version": "2012-10-17",
+///     "Statement": [
+///         { "Sid": "Admin", "Effect": "Allow", "Action": ["iam:*", "iam:GetRole"], "Resource": "*" },
+///         { "Effect": "Allow", "Action": "iam:Get*", "Resource": "*" }
+///     ]
+/// }))
+/// .unwrap();
+///
+/// let drift = detect_drift("policy.json", &policy, &old, &new);
+/// assert_eq!(drift.len(), 1);
+/// assert_eq!(drift[0].patterns[0].patterns, vec!["iam:*"]);
+/// assert_eq!(drift[0].patterns[0].access_levels[0].access_level, "Permissions management");
+/// assert_eq!(drift[0].patterns[0].access_levels[0].actions, vec!["iam:SomethingDangerous"]);
+/// assert!(drift[0].grants());
+/// ```
+pub fn detect_drift(
+    policy_file: &str,
+    policy: &crate::types::AWSPolicyDocument,
+    old: &crate::catalog::Catalog,
+    new: &crate::catalog::Catalog,
+) -> Vec<StatementDrift> {
+    let newly_granted = |pattern: &str| {
+        let previous = old
+            .expand(pattern)
+            .into_iter()
+            .map(|action| action.to_lowercase())
+            .collect::<HashSet<String>>();
+        new.expand(pattern)
+            .into_iter()
+            .filter(|action| !previous.contains(&action.to_lowercase()))
+            .collect::<Vec<String>>()
+    };
+
+    policy
+        .statement
+        .iter()
+        .enumerate()
+        .filter_map(|(statement_index, statement)| {
+            let mut patterns: Vec<PatternDrift> = Vec::new();
+
+            if let Some(action_value) = &statement.action {
+                for pattern in action_value.iter() {
+                    if !crate::pattern::has_wildcard(pattern) {
+                        continue;
+                    }
+                    let actions = newly_granted(pattern);
+                    if !actions.is_empty() {
+                        patterns.push(PatternDrift {
+                            field: "Action".to_string(),
+                            patterns: vec![pattern.to_string()],
+                            access_levels: group_by_access_level(actions, new),
+                        });
+                    }
+                }
+            }
+
+            if let Some(not_action_value) = &statement.not_action {
+                let excluded = not_action_value;
+                let actions = newly_granted("*")
+                    .into_iter()
+                    .filter(|action| {
+                        !excluded
+                            .iter()
+                            .any(|pattern| crate::pattern::wildcard_match(pattern, action))
+                    })
+                    .collect::<Vec<String>>();
+                if !actions.is_empty() {
+                    patterns.push(PatternDrift {
+                        field: "NotAction".to_string(),
+                        patterns: excluded.iter().cloned().collect::<Vec<String>>(),
+                        access_levels: group_by_access_level(actions, new),
+                    });
+                }
+            }
+
+            if patterns.is_empty() {
+                return None;
+            }
+            Some(StatementDrift {
+                policy_file: policy_file.to_string(),
+                statement_index,
+                sid: statement.sid.clone(),
+                effect: statement.effect.clone(),
+                patterns,
+            })
+        })
+        .collect::<Vec<StatementDrift>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{catalog::Catalog, types::AWSPolicyDocument};
+
+    /// Builds an IAM catalog holding the given actions.
+    fn catalog(actions: serde_json::Value) -> Catalog {
+        crate::test_fixtures::catalog(serde_json::json!([{
+            "service": "AWS Identity and Access Management (IAM)",
+            "servicePrefix": "iam",
+            "actions": actions
+        }]))
+    }
+
+    /// Detects the drift of a policy when `iam:PassRole` is added to the catalog.
+    fn drift_of(statements: serde_json::Value) -> Vec<StatementDrift> {
+        let old = catalog(serde_json::json!([{ "action": "iam:GetRole", "type": "Read" }]));
+        let new = catalog(serde_json::json!([
+            { "action": "iam:GetRole", "type": "Read" },
+            { "action": "iam:PassRole", "type": "Write" }
+        ]));
+        let policy = serde_json::from_value::<AWSPolicyDocument>(
+            serde_json::json!({ "Statement": statements }),
+        )
+        .unwrap();
+        detect_drift("policy.json", &policy, &old, &new)
+    }
+
+    #[test]
+    fn allow_wildcards_newly_grant_actions() {
+        let drift = drift_of(serde_json::json!([
+            { "Effect": "Allow", "Action": "iam:*", "Resource": "*" }
+        ]));
+        assert_eq!(drift.len(), 1);
+        assert!(drift[0].grants());
+        assert_eq!(
+            drift[0].patterns[0].access_levels[0].actions,
+            vec!["iam:PassRole"]
+        );
+    }
+
+    #[test]
+    fn allow_not_actions_newly_grant_unexcluded_actions() {
+        let drift = drift_of(serde_json::json!([
+            { "Effect": "Allow", "NotAction": "iam:Get*", "Resource": "*" }
+        ]));
+        assert!(drift[0].grants());
+        assert_eq!(drift[0].patterns[0].field, "NotAction");
+        assert_eq!(drift[0].patterns[0].count(), 1);
+    }
+
+    #[test]
+    fn deny_wildcards_newly_deny_actions() {
+        let drift = drift_of(serde_json::json!([
+            { "Effect": "Deny", "Action": "iam:*", "Resource": "*" }
+        ]));
+        assert_eq!(drift.len(), 1);
+        assert!(!drift[0].grants());
+        assert!(drift[0].to_string().contains("(newly denied)"));
+    }
+}
@@ -0,0 +1,1167 @@
+//! Expansion of the action patterns found in IAM policy statements.
+//!
+//! This module turns the patterns of an `Action` or `NotAction` element into the
+//! concrete actions they grant, and decides what each pattern is rewritten to in an
+//! expanded policy. Full wildcards (`*`) and service wildcards (`service:*`) are
+//! handled according to a selectable [`WildcardMode`], so that admin-style statements
+//! do not silently turn into policies listing every action in the catalog. `NotAction`
+//! elements can also be turned into their complement, the actions a statement effectively
+//! applies to.
+
+use std::collections::{HashMap, HashSet};
+
+/// How full (`*`) and service (`service:*`) wildcards are rewritten in an expanded policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WildcardMode {
+    /// Keep the wildcard untouched.
+    Keep,
+
+    /// Replace the wildcard with every concrete action it matches.
+    Expand,
+
+    /// Replace the wildcard with one `service:*` entry per service it covers.
+    Summary,
+}
+
+/// How `NotAction` elements of `Allow` statements are rewritten in an expanded policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum NotActionMode {
+    /// Expand the excluded patterns, like an `Action` element.
+    Expand,
+
+    /// Replace the `NotAction` element with an `Action` element listing every action in scope
+    /// that is not excluded.
+    Complement,
+}
+
+/// Normalizes an access level for comparison.
+///
+/// Access levels are compared case-insensitively, treating `-` and `_` as spaces so that
+/// they can be typed on the command line (e.g., `permissions-management`).
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::expansion::normalize_access_level;
+///
+/// assert_eq!(normalize_access_level("Permissions management"), "permissions management");
+/// assert_eq!(normalize_access_level("permissions-management"), "permissions management");
+/// ```
+pub fn normalize_access_level(access_level: &str) -> String {
+    access_level.trim().to_lowercase().replace(['-', '_'], " ")
+}
+
+/// A filter on the access level of actions (e.g., "Read", "List", "Write").
+///
+/// An [`Expander`] only applies it to the elements [`Expander::filters`] selects.
+#[derive(Debug, Clone, Default)]
+pub struct AccessLevelFilter {
+    /// The normalized access levels to keep. Empty means every access level.
+    include: Vec<String>,
+
+    /// The normalized access levels to drop.
+    exclude: Vec<String>,
+}
+
+impl AccessLevelFilter {
+    /// Creates a new access level filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `include` - The access levels to keep. Empty means every access level.
+    /// * `exclude` - The access levels to drop.
+    ///
+    /// # Returns
+    ///
+    /// A new `AccessLevelFilter`.
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        AccessLevelFilter {
+            include: include
+                .iter()
+                .map(|level| normalize_access_level(level))
+                .collect(),
+            exclude: exclude
+                .iter()
+                .map(|level| normalize_access_level(level))
+                .collect(),
+        }
+    }
+
+    /// Returns `true` if the filter restricts access levels at all.
+    pub fn is_active(&self) -> bool {
+        !self.include.is_empty() || !self.exclude.is_empty()
+    }
+
+    /// Returns `true` if an action with the given access level passes the filter.
+    ///
+    /// Actions of unknown access level only pass an inactive filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::expansion::AccessLevelFilter;
+    ///
+    /// let filter = AccessLevelFilter::new(&["read".to_string(), "list".to_string()], &[]);
+    /// assert!(filter.allows(Some("Read")));
+    /// assert!(!filter.allows(Some("Write")));
+    ///
+    /// let filter = AccessLevelFilter::new(&[], &["write".to_string()]);
+    /// assert!(filter.allows(Some("Tagging")));
+    /// assert!(!filter.allows(Some("Write")));
+    /// ```
+    pub fn allows(&self, access_level: Option<&str>) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        let Some(access_level) = access_level else {
+            return false;
+        };
+
+        let access_level = normalize_access_level(access_level);
+        (self.include.is_empty() || self.include.contains(&access_level))
+            && !self.exclude.contains(&access_level)
+    }
+}
+
+/// The kind of wildcard a pattern represents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WildcardKind {
+    /// A full wildcard (`*`) matching every action of every service.
+    Full,
+
+    /// A service wildcard (`service:*`) matching every action of one service.
+    Service(String),
+}
+
+impl WildcardKind {
+    /// Classifies a pattern as a full or service wildcard.
+    ///
+    /// # Returns
+    ///
+    /// `Some(WildcardKind)` if the pattern is a full or service wildcard, `None` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::expansion::WildcardKind;
+    ///
+    /// assert_eq!(WildcardKind::of("*"), Some(WildcardKind::Full));
+    /// assert_eq!(WildcardKind::of("S3:*"), Some(WildcardKind::Service("s3".to_string())));
+    /// assert_eq!(WildcardKind::of("s3:Get*"), None);
+    /// ```
+    pub fn of(pattern: &str) -> Option<WildcardKind> {
+        if !pattern.is_empty() && pattern.chars().all(|ch| ch == '*') {
+            return Some(WildcardKind::Full);
+        }
+
+        let (service, action) = pattern.split_once(':')?;
+        if !service.is_empty()
+            && !crate::pattern::has_wildcard(service)
+            && !action.is_empty()
+            && action.chars().all(|ch| ch == '*')
+        {
+            Some(WildcardKind::Service(service.to_lowercase()))
+        } else {
+            None
+        }
+    }
+}
+
+/// The result of expanding a single action pattern.
+#[derive(Debug)]
+pub struct ExpandedPattern {
+    /// The pattern as written in the policy.
+    pub pattern: String,
+
+    /// The concrete actions the pattern matches in the catalog.
+    pub actions: Vec<String>,
+
+    /// The entries the pattern is rewritten to in the expanded policy.
+    ///
+    /// When an access level filter is active, only the matched actions passing it are kept.
+    pub replacement: Vec<String>,
+
+    /// The wildcard kind and the mode it was handled with, if the pattern is a full or
+    /// service wildcard.
+    pub wildcard: Option<(WildcardKind, WildcardMode)>,
+}
+
+/// An action pattern that matched no known action.
+///
+/// Such patterns are typically typos, actions newer than the catalog or unknown services.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UnmatchedPattern {
+    /// The zero-based index of the statement containing the pattern.
+    pub statement_index: usize,
+
+    /// The identifier of the statement containing the pattern, if any.
+    pub sid: Option<String>,
+
+    /// The policy element containing the pattern ("Action" or "NotAction").
+    pub field: String,
+
+    /// The pattern as written in the policy.
+    pub pattern: String,
+}
+
+impl std::fmt::Display for UnmatchedPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} pattern '{}' matched no known actions",
+            statement_label(self.statement_index, self.sid.as_deref()),
+            self.field,
+            self.pattern
+        )
+    }
+}
+
+/// The expansion of an `Action` or `NotAction` element of a policy statement.
+#[derive(Debug)]
+pub struct ExpandedElement {
+    /// The zero-based index of the statement in the policy.
+    pub statement_index: usize,
+
+    /// The identifier of the statement, if any.
+    pub sid: Option<String>,
+
+    /// The effect of the statement ("Allow" or "Deny").
+    pub effect: String,
+
+    /// The name of the policy element ("Action" or "NotAction").
+    pub field: String,
+
+    /// The expanded patterns of the element, in policy order.
+    pub patterns: Vec<ExpandedPattern>,
+
+    /// The concrete actions of the element: the matched actions passing the access level
+    /// filter, or the complement of a `NotAction` element rewritten as its complement.
+    pub actions: Vec<String>,
+
+    /// Whether the access level filter narrowed the element (see [`Expander::filters`]).
+    pub filtered: bool,
+
+    /// The number of actions in the scope of the complement, if the element is a
+    /// `NotAction` element rewritten as its complement.
+    pub complement_scope: Option<usize>,
+}
+
+/// The expansion of a policy document by [`Expander::expand_document`].
+#[derive(Debug, Default)]
+pub struct DocumentExpansion {
+    /// The expanded `Action` and `NotAction` elements, in policy order.
+    pub elements: Vec<ExpandedElement>,
+
+    /// The patterns that matched no known action, in policy order.
+    pub unmatched: Vec<UnmatchedPattern>,
+
+    /// The zero-based indexes of the statements removed for granting no action once
+    /// expanded.
+    pub removed: Vec<usize>,
+}
+
+/// Expands action patterns against the catalog of known IAM actions.
+pub struct Expander<'a> {
+    /// The catalog of known IAM actions.
+    catalog: &'a crate::catalog::Catalog,
+
+    /// How full wildcards (`*`) are rewritten.
+    full_wildcard: WildcardMode,
+
+    /// How service wildcards (`service:*`) are rewritten.
+    service_wildcard: WildcardMode,
+
+    /// The access levels of the actions kept in rewritten entries.
+    access_level_filter: AccessLevelFilter,
+
+    /// How the `NotAction` elements of `Allow` statements are rewritten in documents.
+    not_action_mode: NotActionMode,
+
+    /// The service prefixes making up the scope of `NotAction` complements. Empty means
+    /// the whole catalog.
+    complement_services: Vec<String>,
+}
+
+impl<'a> Expander<'a> {
+    /// Creates a new expander.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The catalog of known IAM actions, whose services are also used to
+    ///   summarise full wildcards per service.
+    /// * `full_wildcard` - How full wildcards (`*`) are rewritten.
+    /// * `service_wildcard` - How service wildcards (`service:*`) are rewritten.
+    ///
+    /// # Returns
+    ///
+    /// A new `Expander` borrowing the given catalog.
+    pub fn new(
+        catalog: &'a crate::catalog::Catalog,
+        full_wildcard: WildcardMode,
+        service_wildcard: WildcardMode,
+    ) -> Self {
+        Expander {
+            catalog,
+            full_wildcard,
+            service_wildcard,
+            access_level_filter: AccessLevelFilter::default(),
+            not_action_mode: NotActionMode::Expand,
+            complement_services: Vec::new(),
+        }
+    }
+
+    /// Selects how the `NotAction` elements of `Allow` statements are rewritten by
+    /// [`Expander::expand_document`].
+    ///
+    /// # Arguments
+    ///
+    /// * `not_action_mode` - How the `NotAction` elements of `Allow` statements are rewritten.
+    /// * `complement_services` - The service prefixes making up the scope of complements. An
+    ///   empty slice means the whole catalog.
+    ///
+    /// # Returns
+    ///
+    /// The expander with the mode applied.
+    pub fn with_not_action_mode(
+        mut self,
+        not_action_mode: NotActionMode,
+        complement_services: &[String],
+    ) -> Self {
+        self.not_action_mode = not_action_mode;
+        self.complement_services = complement_services.to_vec();
+        self
+    }
+
+    /// Restricts the rewritten entries to actions passing an access level filter.
+    ///
+    /// While the filter is active, every wildcard is expanded regardless of the configured
+    /// wildcard modes, since a kept wildcard would also grant the filtered-out actions. The
+    /// filter is meant for the `Action` element of `Allow` statements; use
+    /// [`Expander::for_element`] to expand the elements of a statement.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_level_filter` - The filter the rewritten actions must pass.
+    ///
+    /// # Returns
+    ///
+    /// The expander with the filter applied.
+    pub fn with_access_level_filter(mut self, access_level_filter: AccessLevelFilter) -> Self {
+        self.access_level_filter = access_level_filter;
+        self
+    }
+
+    /// Returns `true` if the access level filter applies to an element of a statement.
+    ///
+    /// Only the `Action` element of `Allow` statements is narrowed by the filter. Dropping
+    /// actions from a `NotAction` element or from a `Deny` statement would make the policy
+    /// grant more, not less, so these elements are expanded unfiltered.
+    ///
+    /// # Arguments
+    ///
+    /// * `effect` - The effect of the statement ("Allow" or "Deny").
+    /// * `field` - The name of the policy element ("Action" or "NotAction").
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::expansion::Expander;
+    ///
+    /// assert!(Expander::filters("Allow", "Action"));
+    /// assert!(!Expander::filters("Allow", "NotAction"));
+    /// assert!(!Expander::filters("Deny", "Action"));
+    /// ```
+    pub fn filters(effect: &str, field: &str) -> bool {
+        effect == "Allow" && field == "Action"
+    }
+
+    /// Returns the expander to use for an element of a statement.
+    ///
+    /// The access level filter is dropped for the elements it does not apply to (see
+    /// [`Expander::filters`]), so that their wildcards are also handled according to the
+    /// configured wildcard modes.
+    ///
+    /// # Arguments
+    ///
+    /// * `effect` - The effect of the statement ("Allow" or "Deny").
+    /// * `field` - The name of the policy element ("Action" or "NotAction").
+    ///
+    /// # Returns
+    ///
+    /// A new `Expander` borrowing the same catalog.
+    pub fn for_element(&self, effect: &str, field: &str) -> Expander<'a> {
+        Expander {
+            catalog: self.catalog,
+            full_wildcard: self.full_wildcard,
+            service_wildcard: self.service_wildcard,
+            access_level_filter: if Self::filters(effect, field) {
+                self.access_level_filter.clone()
+            } else {
+                AccessLevelFilter::default()
+            },
+            not_action_mode: self.not_action_mode,
+            complement_services: self.complement_services.clone(),
+        }
+    }
+
+    /// Returns `true` if an access level filter is set.
+    pub fn is_filtering(&self) -> bool {
+        self.access_level_filter.is_active()
+    }
+
+    /// Expands a single action pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The action pattern as written in the policy.
+    ///
+    /// # Returns
+    ///
+    /// An `ExpandedPattern` with the matched actions and the entries the pattern is
+    /// rewritten to, according to the configured wildcard modes. A pattern matching no
+    /// known action is rewritten to itself.
+    pub fn expand_pattern(&self, pattern: &str) -> ExpandedPattern {
+        let actions = self.catalog.expand(pattern);
+        let kind = WildcardKind::of(pattern);
+
+        let mode = match &kind {
+            _ if self.access_level_filter.is_active() => WildcardMode::Expand,
+            Some(WildcardKind::Full) => self.full_wildcard,
+            Some(WildcardKind::Service(_)) => self.service_wildcard,
+            None => WildcardMode::Expand,
+        };
+
+        let replacement = match (&kind, mode) {
+            // Unmatched patterns are kept verbatim so the policy never grants less than before.
+            _ if actions.is_empty() => vec![pattern.to_string()],
+            (_, WildcardMode::Expand) => self.filter_access_levels(actions.clone()),
+            (_, WildcardMode::Keep) => vec![pattern.to_string()],
+            (Some(WildcardKind::Full), WildcardMode::Summary) => self
+                .catalog
+                .services()
+                .map(|service| format!("{}:*", service.prefix))
+                .collect::<Vec<String>>(),
+            (_, WildcardMode::Summary) => vec![pattern.to_string()],
+        };
+
+        ExpandedPattern {
+            pattern: pattern.to_string(),
+            actions,
+            replacement,
+            wildcard: kind.map(|kind| (kind, mode)),
+        }
+    }
+
+    /// Expands the patterns of an `Action` or `NotAction` element.
+    ///
+    /// # Arguments
+    ///
+    /// * `element` - The patterns of the element, as a single pattern or an array.
+    ///
+    /// # Returns
+    ///
+    /// One `ExpandedPattern` per pattern, in policy order.
+    pub fn expand_element(
+        &self,
+        element: &crate::types::OneOrMany<String>,
+    ) -> Vec<ExpandedPattern> {
+        element
+            .iter()
+            .map(|pattern| self.expand_pattern(pattern))
+            .collect::<Vec<ExpandedPattern>>()
+    }
+
+    /// Expands the `Action` and `NotAction` elements of every statement of a policy
+    /// document in place.
+    ///
+    /// Elements whose entries do not change keep their original form. The access level
+    /// filter is applied as described in [`Expander::filters`], and the statements left
+    /// without any action, by the filter or by an empty complement, are removed rather than
+    /// written with an empty `Action` element IAM would reject. If no statement is left, the
+    /// document has no statement. With [`NotActionMode::Complement`], the `NotAction`
+    /// element of `Allow` statements is replaced with an `Action` element listing its
+    /// complement.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The policy document to expand.
+    ///
+    /// # Returns
+    ///
+    /// The expansion of every element, the patterns that matched no known action and the
+    /// removed statements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::{
+    ///     catalog::Catalog,
+    ///     expansion::{Expander, WildcardMode},
+    ///     types::{AWSPolicyDocument, AwsService},
+    /// };
+    ///
+    /// let catalog = Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([{
+    ///     "service": "AWS Identity and Access Management (IAM)",
+    ///     "servicePrefix": "iam",
+    ///     "actions": [
+    ///         { "action": "iam:CreateRole", "type": "Write" },
+    ///         { "action": "iam:GetRole", "type": "Read" }
+    ///     ]
+    /// }])).unwrap());
+    /// let mut document = serde_json::from_value::<AWSPolicyDocument>(serde_json::json!({
+    ///     "Statement": { "Effect": "Allow", "Action": ["iam:Create*", "iam:Typo"], "Resource": "*" }
+    /// }))
+    /// .unwrap();
+    ///
+    /// let expander = Expander::new(&catalog, WildcardMode::Keep, WildcardMode::Expand);
+    /// let expansion = expander.expand_document(&mut document);
+    /// assert_eq!(expansion.elements[0].actions, vec!["iam:CreateRole"]);
+    /// assert_eq!(expansion.unmatched[0].pattern, "iam:Typo");
+    /// assert_eq!(
+    ///     serde_json::to_value(&document).unwrap()["Statement"]["Action"],
+    ///     serde_json::json!(["iam:CreateRole", "iam:Typo"])
+    /// );
+    /// ```
+    pub fn expand_document(
+        &self,
+        document: &mut crate::types::AWSPolicyDocument,
+    ) -> DocumentExpansion {
+        let mut expansion = DocumentExpansion::default();
+        for (statement_index, statement) in document.statement.iter_mut().enumerate() {
+            let mut emptied = false;
+            if let Some(patterns) = &statement.action {
+                let element =
+                    self.expand_statement_element(statement_index, statement, "Action", patterns);
+                let entries = replacement_entries(&element.patterns);
+                if entries.is_empty() {
+                    emptied = true;
+                } else if !patterns.iter().eq(entries.iter()) {
+                    statement.action = Some(crate::types::OneOrMany::from(entries));
+                }
+                expansion.elements.push(element);
+            }
+
+            if let Some(patterns) = &statement.not_action {
+                let element = self.expand_statement_element(
+                    statement_index,
+                    statement,
+                    "NotAction",
+                    patterns,
+                );
+                if element.complement_scope.is_some() {
+                    emptied |= element.actions.is_empty();
+                    statement.action = Some(crate::types::OneOrMany::from(element.actions.clone()));
+                    statement.not_action = None;
+                    // The complement takes the place of the NotAction element.
+                    statement
+                        .key_order
+                        .iter_mut()
+                        .filter(|key| key.eq_ignore_ascii_case("NotAction"))
+                        .for_each(|key| *key = "Action".to_string());
+                } else {
+                    let entries = replacement_entries(&element.patterns);
+                    if entries.is_empty() {
+                        emptied = true;
+                    } else if !patterns.iter().eq(entries.iter()) {
+                        statement.not_action = Some(crate::types::OneOrMany::from(entries));
+                    }
+                }
+                expansion.elements.push(element);
+            }
+
+            if emptied {
+                expansion.removed.push(statement_index);
+            }
+        }
+
+        expansion.unmatched = expansion
+            .elements
+            .iter()
+            .flat_map(|element| {
+                unmatched_patterns(
+                    &element.patterns,
+                    element.statement_index,
+                    element.sid.as_deref(),
+                    &element.field,
+                )
+            })
+            .collect::<Vec<UnmatchedPattern>>();
+        remove_statements(document, &expansion.removed);
+        expansion
+    }
+
+    /// Expands an `Action` or `NotAction` element of a statement.
+    ///
+    /// # Arguments
+    ///
+    /// * `statement_index` - The zero-based index of the statement in the policy.
+    /// * `statement` - The statement holding the element.
+    /// * `field` - The name of the policy element ("Action" or "NotAction").
+    /// * `patterns` - The patterns of the element.
+    ///
+    /// # Returns
+    ///
+    /// The `ExpandedElement`, holding the complement of a `NotAction` element of an
+    /// `Allow` statement with [`NotActionMode::Complement`].
+    fn expand_statement_element(
+        &self,
+        statement_index: usize,
+        statement: &crate::types::AWSPolicyStatement,
+        field: &str,
+        patterns: &crate::types::OneOrMany<String>,
+    ) -> ExpandedElement {
+        let expander = self.for_element(&statement.effect, field);
+        let patterns = expander.expand_element(patterns);
+
+        let complement = field == "NotAction"
+            && statement.effect == "Allow"
+            && self.not_action_mode == NotActionMode::Complement;
+        let (actions, filtered, complement_scope) = if complement {
+            // The complement becomes the Action element of the statement, which the access
+            // level filter narrows.
+            let expander = self.for_element(&statement.effect, "Action");
+            let (complement, total) = expander.complement(&patterns, &self.complement_services);
+            (complement, expander.is_filtering(), Some(total))
+        } else {
+            (
+                expander.matched_actions(&patterns),
+                expander.is_filtering(),
+                None,
+            )
+        };
+
+        ExpandedElement {
+            statement_index,
+            sid: statement.sid.clone(),
+            effect: statement.effect.clone(),
+            field: field.to_string(),
+            patterns,
+            actions,
+            filtered,
+            complement_scope,
+        }
+    }
+
+    /// Computes the complement of the actions excluded by a `NotAction` element.
+    ///
+    /// # Arguments
+    ///
+    /// * `excluded` - The expanded patterns of the `NotAction` element.
+    /// * `services` - The service prefixes making up the scope of the complement. An empty
+    ///   slice means the whole catalog.
+    ///
+    /// # Returns
+    ///
+    /// A tuple holding every action in scope that is not excluded and passes the access
+    /// level filter, in trie order, and the total number of actions in scope.
+    pub fn complement(
+        &self,
+        excluded: &[ExpandedPattern],
+        services: &[String],
+    ) -> (Vec<String>, usize) {
+        let excluded = excluded
+            .iter()
+            .flat_map(|pattern| pattern.actions.iter().map(|action| action.to_lowercase()))
+            .collect::<HashSet<String>>();
+
+        let scope = if services.is_empty() {
+            self.catalog.expand("*")
+        } else {
+            services
+                .iter()
+                .flat_map(|service| self.catalog.expand(&format!("{}:*", service)))
+                .collect::<Vec<String>>()
+        };
+        let total = scope.len();
+
+        let complement = scope
+            .into_iter()
+            .filter(|action| !excluded.contains(&action.to_lowercase()))
+            .collect::<Vec<String>>();
+
+        (self.filter_access_levels(complement), total)
+    }
+
+    /// Collects the concrete actions granted by the patterns of an element.
+    ///
+    /// # Arguments
+    ///
+    /// * `expanded` - The expanded patterns of an `Action` or `NotAction` element.
+    ///
+    /// # Returns
+    ///
+    /// The matched actions passing the access level filter, deduplicated in policy order.
+    pub fn matched_actions(&self, expanded: &[ExpandedPattern]) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.filter_access_levels(
+            expanded
+                .iter()
+                .flat_map(|pattern| pattern.actions.iter())
+                .filter(|action| seen.insert(action.to_lowercase()))
+                .cloned()
+                .collect::<Vec<String>>(),
+        )
+    }
+
+    /// Keeps the actions passing the configured access level filter.
+    fn filter_access_levels(&self, actions: Vec<String>) -> Vec<String> {
+        actions
+            .into_iter()
+            .filter(|action| {
+                self.access_level_filter
+                    .allows(self.catalog.access_level(action))
+            })
+            .collect::<Vec<String>>()
+    }
+}
+
+/// Removes statements from a policy document.
+///
+/// # Arguments
+///
+/// * `document` - The policy document to remove statements from.
+/// * `indexes` - The zero-based indexes of the statements to remove.
+pub fn remove_statements(document: &mut crate::types::AWSPolicyDocument, indexes: &[usize]) {
+    if indexes.is_empty() {
+        return;
+    }
+    let mut statement_index = 0;
+    document.statement.retain(|_| {
+        statement_index += 1;
+        !indexes.contains(&(statement_index - 1))
+    });
+}
+
+/// Collects the rewritten entries of several expanded patterns.
+///
+/// Entries are deduplicated case-insensitively, keeping the order in which they first
+/// appear.
+///
+/// # Arguments
+///
+/// * `expanded` - The expanded patterns of an `Action` or `NotAction` element.
+///
+/// # Returns
+///
+/// The entries the element is rewritten to in the expanded policy.
+pub fn replacement_entries(expanded: &[ExpandedPattern]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    expanded
+        .iter()
+        .flat_map(|pattern| pattern.replacement.iter())
+        .filter(|entry| seen.insert(entry.to_lowercase()))
+        .cloned()
+        .collect::<Vec<String>>()
+}
+
+/// A pattern of a policy element together with the actions it matched.
+#[derive(Debug, serde::Serialize)]
+pub struct AnnotatedPattern {
+    /// The pattern as written in the policy.
+    pub pattern: String,
+
+    /// The number of concrete actions the pattern matched.
+    pub count: usize,
+
+    /// The concrete actions the pattern matched.
+    pub actions: Vec<String>,
+}
+
+/// A concrete action matched by more than one pattern of the same policy element.
+#[derive(Debug, serde::Serialize)]
+pub struct PatternOverlap {
+    /// The concrete action matched by several patterns.
+    pub action: String,
+
+    /// The patterns matching the action, in policy order.
+    pub patterns: Vec<String>,
+}
+
+/// The annotated expansion of an `Action` or `NotAction` element.
+#[derive(Debug, serde::Serialize)]
+pub struct AnnotatedElement {
+    /// The name of the policy element ("Action" or "NotAction").
+    pub field: String,
+
+    /// Every original pattern with the actions it matched.
+    pub patterns: Vec<AnnotatedPattern>,
+
+    /// The actions matched by more than one pattern of the element.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overlaps: Vec<PatternOverlap>,
+}
+
+/// The annotated expansion of a policy statement.
+#[derive(Debug, serde::Serialize)]
+pub struct AnnotatedStatement {
+    /// The zero-based index of the statement in the policy.
+    pub statement_index: usize,
+
+    /// The identifier of the statement, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+
+    /// The effect of the statement ("Allow" or "Deny").
+    pub effect: String,
+
+    /// The annotated `Action` and `NotAction` elements of the statement.
+    pub elements: Vec<AnnotatedElement>,
+}
+
+/// Annotates the expansion of an `Action` or `NotAction` element.
+///
+/// Each original pattern is listed with the actions it matched, and actions matched by
+/// more than one pattern are flagged as overlaps.
+///
+/// # Arguments
+///
+/// * `expanded` - The expanded patterns of the element.
+/// * `field` - The name of the policy element ("Action" or "NotAction").
+///
+/// # Returns
+///
+/// The `AnnotatedElement` describing the element.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::{
+///     catalog::Catalog,
+///     expansion::{annotate, Expander, WildcardMode},
+///     types::{AwsService, OneOrMany},
+/// };
+///
+/// let catalog = Catalog::new(serde_json::from_value::<Vec<AwsService>>(serde_json::json!([{
+///     "service": "AWS Identity and Access Management (IAM)",
+///     "servicePrefix": "iam",
+///     "actions": [
+///         { "action": "iam:CreateRole", "type": "Write" },
+///         { "action": "iam:CreateUser", "type": "Write" },
+///         { "action": "iam:DeleteRole", "type": "Write" }
+///     ]
+/// }])).unwrap());
+/// let expander = Expander::new(&catalog, WildcardMode::Keep, WildcardMode::Expand);
+///
+/// let element = annotate(
+///     &expander.expand_element(&OneOrMany::Many(vec![
+///         "iam:Create*".to_string(),
+///         "iam:*Role".to_string(),
+///     ])),
+///     "Action",
+/// );
+/// assert_eq!(element.patterns[0].count, 2);
+/// assert_eq!(element.patterns[1].actions, vec!["iam:CreateRole", "iam:DeleteRole"]);
+/// assert_eq!(element.overlaps[0].action, "iam:CreateRole");
+/// assert_eq!(element.overlaps[0].patterns, vec!["iam:Create*", "iam:*Role"]);
+/// ```
+pub fn annotate(expanded: &[ExpandedPattern], field: &str) -> AnnotatedElement {
+    let mut matched_by: Vec<(String, Vec<String>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for pattern in expanded {
+        for action in &pattern.actions {
+            let position = *positions.entry(action.to_lowercase()).or_insert_with(|| {
+                matched_by.push((action.clone(), Vec::new()));
+                matched_by.len() - 1
+            });
+            if !matched_by[position].1.contains(&pattern.pattern) {
+                matched_by[position].1.push(pattern.pattern.clone());
+            }
+        }
+    }
+
+    AnnotatedElement {
+        field: field.to_string(),
+        patterns: expanded
+            .iter()
+            .map(|pattern| AnnotatedPattern {
+                pattern: pattern.pattern.clone(),
+                count: pattern.actions.len(),
+                actions: pattern.actions.clone(),
+            })
+            .collect::<Vec<AnnotatedPattern>>(),
+        overlaps: matched_by
+            .into_iter()
+            .filter(|(_, patterns)| patterns.len() > 1)
+            .map(|(action, patterns)| PatternOverlap { action, patterns })
+            .collect::<Vec<PatternOverlap>>(),
+    }
+}
+
+/// The number of actions of one service granted by an element, per access level.
+#[derive(Debug, serde::Serialize)]
+pub struct ServiceAccessBreakdown {
+    /// The service prefix (e.g., "iam").
+    pub service: String,
+
+    /// The number of granted actions per access level, in [`access_level_order`].
+    pub access_levels: Vec<(String, usize)>,
+
+    /// The number of granted actions of the service.
+    pub granted: usize,
+
+    /// The total number of actions of the service in the catalog.
+    pub total: usize,
+}
+
+impl ServiceAccessBreakdown {
+    /// Returns the percentage of the service's actions that are granted.
+    pub fn percentage(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.granted as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+impl std::fmt::Display for ServiceAccessBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let access_levels = self
+            .access_levels
+            .iter()
+            .map(|(access_level, count)| format!("{} {}", count, access_level))
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(
+            f,
+            "{}: {} ({} of {} actions, {:.1}%)",
+            self.service,
+            access_levels,
+            self.granted,
+            self.total,
+            self.percentage()
+        )
+    }
+}
+
+/// Returns the sort key of an access level, ordering the IAM levels from the least to the
+/// most sensitive: Read, List, Write, Tagging, Permissions management, then any other level.
+pub fn access_level_order(access_level: &str) -> (usize, String) {
+    let normalized = normalize_access_level(access_level);
+    let rank = match normalized.as_str() {
+        "read" => 0,
+        "list" => 1,
+        "write" => 2,
+        "tagging" => 3,
+        "permissions management" | "permission management" => 4,
+        _ => 5,
+    };
+    (rank, normalized)
+}
+
+/// Breaks down a list of granted actions per service and access level.
+///
+/// # Arguments
+///
+/// * `actions` - The concrete actions granted by an element.
+/// * `index` - The index of every known IAM action, holding their access levels.
+///
+/// # Returns
+///
+/// One `ServiceAccessBreakdown` per service, sorted by service prefix.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::{expansion::access_breakdown, pattern::ActionIndex, types::AwsService};
+///
+/// let services = serde_json::from_value::<Vec<AwsService>>(serde_json::json!([{
+///     "service": "AWS Identity and Access Management (IAM)",
+///     "servicePrefix": "iam",
+///     "actions": [
+///         { "action": "iam:GetRole", "type": "Read" },
+///         { "action": "iam:CreateRole", "type": "Write" },
+///         { "action": "iam:AttachRolePolicy", "type": "Permissions management" },
+///         { "action": "iam:DeleteRole", "type": "Write" }
+///     ]
+/// }]))
+/// .unwrap();
+/// let index = ActionIndex::from_services(&services);
+///
+/// let breakdown = access_breakdown(
+///     &["iam:GetRole".to_string(), "iam:AttachRolePolicy".to_string()],
+///     &index,
+/// );
+/// assert_eq!(
+///     breakdown[0].to_string(),
+///     "iam: 1 Read, 1 Permissions management (2 of 4 actions, 50.0%)"
+/// );
+/// ```
+pub fn access_breakdown(
+    actions: &[String],
+    index: &crate::pattern::ActionIndex,
+) -> Vec<ServiceAccessBreakdown> {
+    let mut services: std::collections::BTreeMap<String, HashMap<String, usize>> =
+        std::collections::BTreeMap::new();
+    for action in actions {
+        let service = action
+            .split_once(':')
+            .map(|(service, _)| service.to_lowercase())
+            .unwrap_or_default();
+        let access_level = index.access_level(action).unwrap_or("Unknown").to_string();
+        *services
+            .entry(service)
+            .or_default()
+            .entry(access_level)
+            .or_default() += 1;
+    }
+
+    services
+        .into_iter()
+        .map(|(service, access_levels)| {
+            let mut access_levels = access_levels.into_iter().collect::<Vec<(String, usize)>>();
+            access_levels.sort_by_key(|(access_level, _)| access_level_order(access_level));
+
+            ServiceAccessBreakdown {
+                granted: access_levels.iter().map(|(_, count)| count).sum(),
+                total: index.expand(&format!("{}:*", service)).len(),
+                service,
+                access_levels,
+            }
+        })
+        .collect::<Vec<ServiceAccessBreakdown>>()
+}
+
+/// Collects the patterns of an element that matched no known action.
+///
+/// # Arguments
+///
+/// * `expanded` - The expanded patterns of an `Action` or `NotAction` element.
+/// * `statement_index` - The zero-based index of the statement in the policy.
+/// * `sid` - The optional statement identifier.
+/// * `field` - The name of the policy element ("Action" or "NotAction").
+///
+/// # Returns
+///
+/// One `UnmatchedPattern` per pattern without matches, in policy order.
+pub fn unmatched_patterns(
+    expanded: &[ExpandedPattern],
+    statement_index: usize,
+    sid: Option<&str>,
+    field: &str,
+) -> Vec<UnmatchedPattern> {
+    expanded
+        .iter()
+        .filter(|pattern| pattern.actions.is_empty())
+        .map(|pattern| UnmatchedPattern {
+            statement_index,
+            sid: sid.map(str::to_string),
+            field: field.to_string(),
+            pattern: pattern.pattern.clone(),
+        })
+        .collect::<Vec<UnmatchedPattern>>()
+}
+
+/// Builds the label identifying a policy statement in reports.
+///
+/// # Arguments
+///
+/// * `statement_index` - The zero-based index of the statement in the policy.
+/// * `sid` - The optional statement identifier.
+///
+/// # Returns
+///
+/// A label such as `Statement 1 (Sid: AllowS3)` or `Statement 0`.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::expansion::statement_label;
+///
+/// assert_eq!(statement_label(1, Some("AllowS3")), "Statement 1 (Sid: AllowS3)");
+/// assert_eq!(statement_label(0, None), "Statement 0");
+/// ```
+pub fn statement_label(statement_index: usize, sid: Option<&str>) -> String {
+    match sid {
+        Some(sid) => format!("Statement {} (Sid: {})", statement_index, sid),
+        None => format!("Statement {}", statement_index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{catalog::Catalog, test_fixtures::fixture_catalog, types::AWSPolicyDocument};
+
+    /// Expands a single-statement policy keeping only the Read actions.
+    fn expand_read_only(catalog: &Catalog, statement: serde_json::Value) -> serde_json::Value {
+        let mut document = serde_json::from_value::<AWSPolicyDocument>(
+            serde_json::json!({ "Statement": [statement] }),
+        )
+        .unwrap();
+        Expander::new(catalog, WildcardMode::Keep, WildcardMode::Keep)
+            .with_access_level_filter(AccessLevelFilter::new(&["read".to_string()], &[]))
+            .expand_document(&mut document);
+        serde_json::to_value(&document).unwrap()["Statement"].clone()
+    }
+
+    #[test]
+    fn access_level_filter_narrows_allow_actions() {
+        let catalog = fixture_catalog();
+        let statements = expand_read_only(
+            &catalog,
+            serde_json::json!({ "Effect": "Allow", "Action": "iam:*", "Resource": "*" }),
+        );
+        assert_eq!(statements[0]["Action"], serde_json::json!(["iam:GetRole"]));
+    }
+
+    #[test]
+    fn access_level_filter_keeps_allow_not_actions() {
+        let catalog = fixture_catalog();
+        let statements = expand_read_only(
+            &catalog,
+            serde_json::json!({ "Effect": "Allow", "NotAction": ["iam:*"], "Resource": "*" }),
+        );
+        // Narrowing the exclusion to iam:GetRole would grant iam:PassRole.
+        assert_eq!(statements[0]["NotAction"], serde_json::json!(["iam:*"]));
+    }
+
+    #[test]
+    fn access_level_filter_keeps_deny_actions() {
+        let catalog = fixture_catalog();
+        let statements = expand_read_only(
+            &catalog,
+            serde_json::json!({ "Effect": "Deny", "Action": "s3:*", "Resource": "*" }),
+        );
+        // Narrowing the Deny to s3:GetObject would stop denying s3:PutObject.
+        assert_eq!(statements[0]["Action"], serde_json::json!("s3:*"));
+    }
+
+    #[test]
+    fn access_level_filter_removes_emptied_statements() {
+        let catalog = fixture_catalog();
+        let mut document = serde_json::from_value::<AWSPolicyDocument>(serde_json::json!({
+            "Statement": [
+                { "Effect": "Allow", "Action": "iam:Create*", "Resource": "*" },
+                { "Effect": "Allow", "Action": "s3:*", "Resource": "*" }
+            ]
+        }))
+        .unwrap();
+        let expansion = Expander::new(&catalog, WildcardMode::Keep, WildcardMode::Keep)
+            .with_access_level_filter(AccessLevelFilter::new(&["read".to_string()], &[]))
+            .expand_document(&mut document);
+        assert_eq!(expansion.removed, vec![0]);
+
+        let statements = serde_json::to_value(&document).unwrap()["Statement"].clone();
+        assert_eq!(
+            statements,
+            serde_json::json!([{ "Effect": "Allow", "Action": ["s3:GetObject"], "Resource": "*" }])
+        );
+    }
+
+    #[test]
+    fn complement_excludes_not_action_patterns() {
+        let catalog = fixture_catalog();
+        let expander = Expander::new(&catalog, WildcardMode::Keep, WildcardMode::Keep);
+        let excluded = expander.expand_element(&crate::types::OneOrMany::One("iam:*".to_string()));
+
+        let (complement, total) = expander.complement(&excluded, &["sts".to_string()]);
+        assert_eq!(complement, vec!["sts:AssumeRole", "sts:GetCallerIdentity"]);
+        assert_eq!(total, 2);
+
+        let (complement, total) = expander.complement(&excluded, &[]);
+        assert_eq!(complement.len(), 8);
+        assert_eq!(total, 15);
+    }
+}
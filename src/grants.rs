@@ -0,0 +1,144 @@
+//! Reverse lookup from concrete actions to the policy statements covering them.
+//!
+//! This module answers the question "which of our policies let someone do X?" by
+//! matching a concrete action against the `Action` and `NotAction` patterns of every
+//! statement of a policy, using the same IAM wildcard semantics as the expansion.
+
+/// A policy statement whose action patterns cover a concrete action.
+#[derive(Debug, serde::Serialize)]
+pub struct StatementGrant {
+    /// The policy file containing the statement.
+    pub policy_file: String,
+
+    /// The zero-based index of the statement in the policy.
+    pub statement_index: usize,
+
+    /// The identifier of the statement, if any.
+    pub sid: Option<String>,
+
+    /// The effect of the statement ("Allow" or "Deny").
+    pub effect: String,
+
+    /// The concrete action covered by the statement.
+    pub action: String,
+
+    /// The policy element covering the action ("Action" or "NotAction").
+    pub field: String,
+
+    /// The patterns responsible for the match.
+    ///
+    /// For an `Action` element these are the patterns matching the action. For a
+    /// `NotAction` element, which covers every action it does not exclude, these are the
+    /// excluded patterns none of which match the action.
+    pub patterns: Vec<String>,
+}
+
+impl std::fmt::Display for StatementGrant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|pattern| format!("'{}'", pattern))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let label = crate::expansion::statement_label(self.statement_index, self.sid.as_deref());
+
+        if self.field == "NotAction" {
+            write!(
+                f,
+                "{} {}: {} via NotAction (not excluded by {})",
+                self.policy_file, label, self.effect, patterns
+            )
+        } else {
+            write!(
+                f,
+                "{} {}: {} via Action {}",
+                self.policy_file, label, self.effect, patterns
+            )
+        }
+    }
+}
+
+/// Finds the statements of a policy whose action patterns cover a concrete action.
+///
+/// A statement covers the action if one of its `Action` patterns matches it, or if it has
+/// a `NotAction` element none of whose patterns match it.
+///
+/// # Arguments
+///
+/// * `policy_file` - The name of the policy file, used to identify the statements.
+/// * `policy` - The parsed policy document.
+/// * `action` - The concrete action to look up (e.g., `iam:PassRole`).
+///
+/// # Returns
+///
+/// One `StatementGrant` per covering statement, in policy order.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::{grants::find_grants, types::AWSPolicyDocument};
+///
+/// let policy = serde_json::from_value::<AWSPolicyDocument>(serde_json::json!({
+///     "Version": "2012-10-17",
+///     "Statement": [
+///         { "Sid": "Pass", "Effect": "Allow", "Action": ["iam:Pass*", "s3:*"], "Resource": "*" },
+///         { "Effect": "Deny", "NotAction": "iam:*", "Resource": "*" },
+///         { "Effect": "Allow", "NotAction": "s3:*", "Resource": "*" }
+///     ]
+/// }))
+/// .unwrap();
+///
+/// let grants = find_grants("policy.json", &policy, "iam:PassRole");
+/// assert_eq!(grants.len(), 2);
+/// assert_eq!(grants[0].sid.as_deref(), Some("Pass"));
+/// assert_eq!(grants[0].patterns, vec!["iam:Pass*"]);
+/// assert_eq!(grants[1].statement_index, 2);
+/// assert_eq!(grants[1].field, "NotAction");
+/// ```
+pub fn find_grants(
+    policy_file: &str,
+    policy: &crate::types::AWSPolicyDocument,
+    action: &str,
+) -> Vec<StatementGrant> {
+    policy
+        .statement
+        .iter()
+        .enumerate()
+        .filter_map(|(statement_index, statement)| {
+            let (field, patterns) = if let Some(action_value) = &statement.action {
+                let matching = action_value
+                    .iter()
+                    .filter(|pattern| crate::pattern::wildcard_match(pattern, action))
+                    .cloned()
+                    .collect::<Vec<String>>();
+                if matching.is_empty() {
+                    return None;
+                }
+                ("Action", matching)
+            } else {
+                let excluded = statement.not_action.as_ref()?;
+                if excluded
+                    .iter()
+                    .any(|pattern| crate::pattern::wildcard_match(pattern, action))
+                {
+                    return None;
+                }
+                (
+                    "NotAction",
+                    excluded.iter().cloned().collect::<Vec<String>>(),
+                )
+            };
+
+            Some(StatementGrant {
+                policy_file: policy_file.to_string(),
+                statement_index,
+                sid: statement.sid.clone(),
+                effect: statement.effect.clone(),
+                action: action.to_string(),
+                field: field.to_string(),
+                patterns,
+            })
+        })
+        .collect::<Vec<StatementGrant>>()
+}
@@ -12,41 +12,89 @@
 //! - List all available AWS services and their service prefixes
 //! - Expand IAM actions for a specific service, optionally filtered by prefix
 //! - Efficiently search for actions using trie-based prefix matching
+//! - Find the policy statements granting a concrete action
+//! - Compress concrete action lists into minimal sets of safe wildcards
+//! - Compare two catalog snapshots into a changelog of added, removed and reclassified actions
+//! - Detect the new actions policy wildcards grant under a newer catalog
+//! - Report deprecated and removed actions referenced by policies, with known replacements
+//! - Parse every policy IAM accepts, and locate the problems of those it rejects
+//! - Find and expand the policies embedded in CloudFormation templates and Terraform plans
+//! - Match IAM wildcard patterns (`*` and `?`) anywhere in an action name, ignoring case
+//! - Load resource types, condition keys and dependent actions from the AWS Service
+//!   Authorization Reference
+//! - Load the catalog from pluggable sources (awsiamactions.io, the Service Authorization
+//!   Reference, policy_sentry or local files)
 //!
 //! # Modules
 //!
+//! - [`catalog`]: The catalog of known AWS IAM services and actions, with its lookup indexes
+//! - [`cloudformation`]: Extraction of the policy documents embedded in CloudFormation templates
 //! - [`command`]: Command-line interface and argument parsing
-//! - [`types`]: Core data structures for AWS services and actions
+//! - [`compress`]: Compression of concrete action lists into minimal sets of safe wildcards
+//! - [`diff`]: Comparison of two catalogs of AWS IAM services and actions
+//! - [`drift`]: Wildcard drift detection across catalog versions
+//! - [`expansion`]: Expansion of policy action patterns, including full and service wildcards
+//! - [`grants`]: Reverse lookup from concrete actions to the policy statements covering them
+//! - [`pattern`]: IAM wildcard matching and pattern expansion
+//! - [`policy`]: Parsing of AWS IAM policy documents with located diagnostics
+//! - [`policy_sentry`]: Loader for the policy_sentry IAM definition database
+//! - [`retired`]: Detection of deprecated, renamed and removed actions
+//! - [`service_reference`]: Loader for the AWS Service Authorization Reference JSON format
+//! - [`source`]: Pluggable sources for the catalog of AWS IAM services and actions
+//! - [`terraform`]: Extraction of the policy documents of Terraform plans
+//! - [`types`]: Core data structures for AWS services, actions and policy documents
 //! - [`utils`]: Utility functions for fetching and caching IAM actions data
 //!
 //! # Examples
 //!
 //! ```no_run
-//! use std::collections::HashMap;
-//! use aws_iam_expansion::{command::Args, types::AwsService, utils::retrieve_iam_actions_json};
+//! use aws_iam_expansion::{catalog::Catalog, command::Args};
 //! use clap::Parser;
 //!
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! // Parse command-line arguments
 //! let cli_args = Args::parse();
 //!
-//! // Retrieve AWS IAM actions (cached if available)
-//! let iam_actions_json = retrieve_iam_actions_json()?;
-//!
-//! // Deserialize and organize by service prefix
-//! let mut services: HashMap<String, Vec<AwsService>> = HashMap::new();
-//! for service in serde_json::from_str::<Vec<AwsService>>(&iam_actions_json)? {
-//!     services.entry(service.prefix.clone())
-//!         .or_insert_with(Vec::new)
-//!         .push(service);
-//! }
+//! // Load the catalog from the selected source (cached if available)
+//! let catalog = Catalog::from_source(cli_args.catalog_source()?.as_ref())?;
 //!
 //! // Handle the command
-//! cli_args.handle(services)?;
+//! cli_args.handle(&catalog)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Tools embedding the crate can query a catalog directly:
+//!
+//! ```no_run
+//! use aws_iam_expansion::{catalog::Catalog, source::AwsIamActionsSource};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let catalog = Catalog::from_source(&AwsIamActionsSource::default())?;
+//! for action in catalog.expand("iam:*Policy*") {
+//!     println!("{} ({})", action, catalog.access_level(&action).unwrap_or("Unknown"));
+//! }
+//! println!("{} permissions management actions", catalog.by_access_level("permissions-management").len());
 //! # Ok(())
 //! # }
 //! ```
 
+pub mod catalog;
+pub mod cloudformation;
 pub mod command;
+pub mod compress;
+pub mod diff;
+pub mod drift;
+pub mod expansion;
+pub mod grants;
+pub mod pattern;
+pub mod policy;
+pub mod policy_sentry;
+pub mod retired;
+pub mod service_reference;
+pub mod source;
+pub mod terraform;
+#[cfg(test)]
+mod test_fixtures;
 pub mod types;
 pub mod utils;
@@ -5,10 +5,17 @@
 //!
 //! # Overview
 //!
-//! The application fetches AWS IAM actions data from the AWS IAM Actions API, caches it locally,
-//! and provides commands to:
-//! - List all available AWS services
+//! The application loads the catalog of AWS IAM services and actions from the selected
+//! source (by default, the AWS IAM Actions API), caches it locally, and provides commands to:
+//! - List and search the available AWS services
 //! - Expand IAM actions for a specific service with optional prefix filtering
+//! - Describe actions, with their resource types, condition keys and dependent actions
+//! - Expand the wildcards of a local JSON or YAML policy file
+//! - Find the policy statements granting an action
+//! - Compress actions into minimal sets of safe wildcards
+//! - Compare catalog snapshots and detect the wildcard drift of policies between them
+//! - Expand the policies embedded in CloudFormation templates and Terraform plans
+//! - Delete, update and inspect the local cache
 //!
 //! # Usage
 //!
@@ -21,11 +28,10 @@
 //!
 //! # Expand actions matching a prefix
 //! cargo run -- expand --service-name iam --prefix Create
-//! ```
-//!
-//! # Todo
 //!
-//! - Add option to read AWS IAM policy or Role from a local file for direct policy expansion.
+//! # Expand the wildcards of a policy file
+//! cargo run -- expand-file --policy-file policy.json
+//! ```
 
 use clap::Parser;
 
@@ -33,10 +39,11 @@ use clap::Parser;
 ///
 /// This function orchestrates the entire application flow:
 /// 1. Parses command-line arguments using the `clap` crate
-/// 2. Retrieves AWS IAM actions data (from cache or API)
-/// 3. Deserializes the JSON data into `AwsService` structures
-/// 4. Organizes services by their service prefix in a HashMap
-/// 5. Delegates command handling to the parsed arguments
+/// 2. Unless the command only manages the cache, loads AWS IAM actions data from the
+///    selected catalog source (by default, from cache or the AWS IAM Actions API),
+///    normalized into `AwsService` structures
+/// 3. Builds the `Catalog`, merging services sharing a prefix and indexing their actions
+/// 4. Delegates command handling to the parsed arguments
 ///
 /// # Returns
 ///
@@ -51,25 +58,9 @@ use clap::Parser;
 ///
 /// The function can return errors from:
 /// - Network requests (if cache doesn't exist)
-/// - File system operations (cache read/write)
-/// - JSON parsing of the IAM actions data
+/// - File system operations (cache read/write, local catalog sources)
+/// - Parsing of the configuration file or the IAM actions data
 /// - Service lookup or action expansion
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli_args = aws_iam_expansion::command::Args::parse();
-    let iam_actions_json = aws_iam_expansion::utils::retrieve_iam_actions_json()?;
-    let mut available_services_permissions: std::collections::HashMap<
-        String,
-        Vec<aws_iam_expansion::types::AwsService>,
-    > = std::collections::HashMap::new();
-
-    for service in
-        serde_json::from_str::<Vec<aws_iam_expansion::types::AwsService>>(&iam_actions_json)?
-    {
-        available_services_permissions
-            .entry(service.prefix.clone())
-            .or_insert_with(Vec::new)
-            .push(service);
-    }
-
-    cli_args.handle(available_services_permissions)
+    aws_iam_expansion::command::Args::parse().run()
 }
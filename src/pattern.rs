@@ -0,0 +1,255 @@
+//! IAM action pattern matching.
+//!
+//! This module implements the wildcard semantics IAM applies to the `Action` and
+//! `NotAction` elements of a policy: `*` matches any sequence of characters (including
+//! none) and `?` matches exactly one character, anywhere in the action name. Like IAM,
+//! matching is case-insensitive. The trie of known actions is used to narrow the
+//! candidates down to the literal prefix that precedes the first wildcard before the
+//! full pattern is applied.
+
+use std::collections::HashMap;
+
+/// Returns `true` if the given pattern contains an IAM wildcard (`*` or `?`).
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::pattern::has_wildcard;
+///
+/// assert!(has_wildcard("s3:Get*"));
+/// assert!(has_wildcard("ec2:Describe?nstances"));
+/// assert!(!has_wildcard("iam:PassRole"));
+/// ```
+pub fn has_wildcard(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Returns the literal part of a pattern that precedes its first wildcard.
+///
+/// For a pattern without wildcards the whole pattern is returned.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::pattern::literal_prefix;
+///
+/// assert_eq!(literal_prefix("ec2:Describe*Attribute"), "ec2:Describe");
+/// assert_eq!(literal_prefix("s3:*Tagging"), "s3:");
+/// assert_eq!(literal_prefix("*"), "");
+/// assert_eq!(literal_prefix("iam:PassRole"), "iam:PassRole");
+/// ```
+pub fn literal_prefix(pattern: &str) -> &str {
+    match pattern.find(['*', '?']) {
+        Some(index) => &pattern[..index],
+        None => pattern,
+    }
+}
+
+/// Matches a candidate action name against an IAM wildcard pattern.
+///
+/// `*` matches any sequence of characters (including the empty sequence) and `?`
+/// matches exactly one character. Every other character must match literally, ignoring
+/// ASCII case.
+///
+/// # Arguments
+///
+/// * `pattern` - The IAM action pattern (e.g., `s3:*Object`, `ec2:Describe*Attribute`).
+/// * `candidate` - The concrete action name to test (e.g., `s3:GetObject`).
+///
+/// # Returns
+///
+/// `true` if the whole candidate is matched by the pattern.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::pattern::wildcard_match;
+///
+/// assert!(wildcard_match("s3:*Tagging", "s3:GetObjectTagging"));
+/// assert!(wildcard_match("ec2:Describe*Attribute", "ec2:DescribeInstanceAttribute"));
+/// assert!(wildcard_match("iam:?etRole", "iam:GetRole"));
+/// assert!(wildcard_match("S3:getobject", "s3:GetObject"));
+/// assert!(!wildcard_match("s3:*Object", "s3:GetObjectTagging"));
+/// assert!(!wildcard_match("iam:?etRole", "iam:ListRole"));
+/// ```
+pub fn wildcard_match(pattern: &str, candidate: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let candidate: Vec<char> = candidate.chars().collect();
+
+    let (mut p, mut c) = (0, 0);
+    // Position of the last `*` seen in the pattern and the candidate position it
+    // was tried against, used to backtrack when a later literal fails to match.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while c < candidate.len() {
+        if p < pattern.len()
+            && (pattern[p] == '?' || pattern[p].eq_ignore_ascii_case(&candidate[c]))
+        {
+            p += 1;
+            c += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, c));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            c = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&ch| ch == '*')
+}
+
+/// An index over the known IAM actions supporting case-insensitive pattern expansion.
+///
+/// Action names are stored case-folded in a trie, alongside a map back to their
+/// canonical casing, so that `S3:getobject` or `iam:create*` match the same actions as
+/// `s3:GetObject` and `iam:Create*` while results keep the casing from
+/// [`AwsAction::name`](crate::types::AwsAction::name).
+pub struct ActionIndex {
+    /// Trie of case-folded action names used for literal prefix searches.
+    trie: trie_rs::Trie<u8>,
+
+    /// Map from case-folded action names to their canonical casing.
+    canonical: HashMap<String, String>,
+
+    /// Map from case-folded action names to their access level (e.g., "Read", "Write").
+    access_levels: HashMap<String, String>,
+}
+
+impl ActionIndex {
+    /// Builds an index from an iterator of canonical action names.
+    ///
+    /// # Arguments
+    ///
+    /// * `actions` - The canonical action names (e.g., `s3:GetObject`).
+    ///
+    /// # Returns
+    ///
+    /// A new `ActionIndex` covering every given action.
+    pub fn new<I, S>(actions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let canonical = actions
+            .into_iter()
+            .map(|action| (action.as_ref().to_lowercase(), action.as_ref().to_string()))
+            .collect::<HashMap<String, String>>();
+        let trie = trie_rs::Trie::from_iter(canonical.keys());
+
+        ActionIndex {
+            trie,
+            canonical,
+            access_levels: HashMap::new(),
+        }
+    }
+
+    /// Builds an index from a list of services.
+    ///
+    /// # Arguments
+    ///
+    /// * `services` - The services whose actions to index, typically those of a
+    ///   [`Catalog`](crate::catalog::Catalog).
+    ///
+    /// # Returns
+    ///
+    /// A new `ActionIndex` covering every action of every service, including their access levels.
+    /// An action name listed by several services keeps the access level of the first one,
+    /// like [`Catalog::access_level`](crate::catalog::Catalog::access_level).
+    pub fn from_services<'s, I>(services: I) -> Self
+    where
+        I: IntoIterator<Item = &'s crate::types::AwsService>,
+    {
+        let actions = services
+            .into_iter()
+            .flat_map(|service| service.actions.iter())
+            .collect::<Vec<&crate::types::AwsAction>>();
+
+        let mut index = Self::new(actions.iter().map(|action| action.name.as_str()));
+        for action in actions {
+            index
+                .access_levels
+                .entry(action.name.to_lowercase())
+                .or_insert_with(|| action.action_type.clone());
+        }
+        index
+    }
+
+    /// Returns the access level of a concrete action (e.g., "Read", "Write"), if it is known.
+    ///
+    /// Access levels are only available for indexes built with [`ActionIndex::from_services`].
+    pub fn access_level(&self, action: &str) -> Option<&str> {
+        self.access_levels
+            .get(&action.to_lowercase())
+            .map(|access_level| access_level.as_str())
+    }
+
+    /// Returns the canonical casing of a concrete action name, if it is known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::pattern::ActionIndex;
+    ///
+    /// let index = ActionIndex::new(["s3:GetObject"]);
+    /// assert_eq!(index.canonical_name("S3:GETOBJECT"), Some("s3:GetObject"));
+    /// assert_eq!(index.canonical_name("s3:PutObject"), None);
+    /// ```
+    pub fn canonical_name(&self, action: &str) -> Option<&str> {
+        self.canonical
+            .get(&action.to_lowercase())
+            .map(|action| action.as_str())
+    }
+
+    /// Expands an IAM action pattern into the concrete actions it matches.
+    ///
+    /// The trie is queried with the case-folded literal prefix preceding the first
+    /// wildcard, and the candidates it returns are then filtered with [`wildcard_match`].
+    /// A pattern without wildcards only matches the identical action name.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The IAM action pattern to expand (e.g., `s3:*Tagging`).
+    ///
+    /// # Returns
+    ///
+    /// A vector with every matching action in its canonical casing, in trie order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::pattern::ActionIndex;
+    ///
+    /// let index = ActionIndex::new([
+    ///     "s3:GetObject",
+    ///     "s3:GetObjectTagging",
+    ///     "s3:PutObjectTagging",
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     index.expand("s3:*Tagging"),
+    ///     vec!["s3:GetObjectTagging", "s3:PutObjectTagging"]
+    /// );
+    /// assert_eq!(index.expand("S3:getobject"), vec!["s3:GetObject"]);
+    /// assert!(index.expand("s3:DeleteObject").is_empty());
+    /// ```
+    pub fn expand(&self, pattern: &str) -> Vec<String> {
+        let pattern = pattern.to_lowercase();
+        if !has_wildcard(&pattern) {
+            return self
+                .canonical
+                .get(&pattern)
+                .map(|action| vec![action.clone()])
+                .unwrap_or_default();
+        }
+
+        self.trie
+            .predictive_search(literal_prefix(&pattern))
+            .filter(|action: &String| wildcard_match(&pattern, action))
+            .map(|action: String| self.canonical[&action].clone())
+            .collect::<Vec<String>>()
+    }
+}
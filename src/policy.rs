@@ -0,0 +1,754 @@
+//! Parsing of AWS IAM policy documents with located diagnostics.
+//!
+//! [`AWSPolicyDocument`](crate::types::AWSPolicyDocument) accepts the grammar IAM accepts,
+//! but a document IAM would reject only produces a generic serde error. This module checks
+//! a document element by element against the source text first, so that such a document is
+//! reported with the line and column of the offending element and the statement it belongs
+//! to.
+//!
+//! Policies can be written in JSON or YAML. A [`PolicyFile`] keeps the format of the
+//! document and the comments heading a YAML document, so that it can be written back in the
+//! same form. Comments inside a YAML document are not kept: the document is rewritten from
+//! its parsed form, which has no place for them.
+
+use crate::types::{AWSPolicyDocument, DOCUMENT_ELEMENTS, STATEMENT_ELEMENTS, canonical_element};
+use serde_json::value::RawValue;
+
+/// The policy language versions IAM accepts.
+const POLICY_VERSIONS: &[&str] = &["2012-10-17", "2008-10-17"];
+
+/// The elements of a JSON object, in document order, borrowed from the source text.
+type RawObject<'a> = indexmap::IndexMap<String, &'a RawValue>;
+
+/// A problem found in a policy document, located in its source text.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PolicyDiagnostic {
+    /// The one-based line of the offending element, or 0 if the problem cannot be located
+    /// (e.g., a problem found after a YAML document was converted).
+    pub line: usize,
+
+    /// The one-based column of the offending element, or 0 if the problem cannot be located.
+    pub column: usize,
+
+    /// The label of the offending statement (e.g., "Statement 1 (Sid: AllowS3)"), if the
+    /// problem lies within a statement.
+    pub statement: Option<String>,
+
+    /// The description of the problem.
+    pub message: String,
+}
+
+impl std::fmt::Display for PolicyDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line != 0 {
+            write!(f, "line {}, column {}: ", self.line, self.column)?;
+        }
+        if let Some(statement) = &self.statement {
+            write!(f, "{}: ", statement)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for PolicyDiagnostic {}
+
+/// Tracks where diagnostics are located while checking a policy document.
+struct Locator<'a> {
+    /// The source text of the policy document.
+    source: &'a str,
+}
+
+impl<'a> Locator<'a> {
+    /// Returns the one-based line and column of a byte offset in the source text.
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let before = &self.source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = offset - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+        (line, column)
+    }
+
+    /// Returns the byte offset of an element borrowed from the source text.
+    fn offset(&self, raw: &RawValue) -> usize {
+        raw.get().as_ptr() as usize - self.source.as_ptr() as usize
+    }
+
+    /// Builds a diagnostic located at the start of an element.
+    fn at(&self, raw: &RawValue, statement: Option<&str>, message: String) -> PolicyDiagnostic {
+        self.at_offset(self.offset(raw), statement, message)
+    }
+
+    /// Builds a diagnostic located at a byte offset of the source text.
+    fn at_offset(
+        &self,
+        offset: usize,
+        statement: Option<&str>,
+        message: String,
+    ) -> PolicyDiagnostic {
+        let (line, column) = self.position(offset);
+        PolicyDiagnostic {
+            line,
+            column,
+            statement: statement.map(|statement| statement.to_string()),
+            message,
+        }
+    }
+
+    /// Builds a diagnostic from an error raised while parsing the text starting at a byte
+    /// offset of the source text, locating it relative to that offset.
+    fn at_error(
+        &self,
+        offset: usize,
+        statement: Option<&str>,
+        error: &serde_json::Error,
+    ) -> PolicyDiagnostic {
+        let mut message = error.to_string();
+        if error.line() == 0 {
+            return self.at_offset(offset, statement, message);
+        }
+        message.truncate(
+            message
+                .strip_suffix(&format!(
+                    " at line {} column {}",
+                    error.line(),
+                    error.column()
+                ))
+                .map_or(message.len(), str::len),
+        );
+        let (line, column) = self.position(offset);
+        PolicyDiagnostic {
+            line: line + error.line() - 1,
+            column: if error.line() == 1 {
+                column + error.column() - 1
+            } else {
+                error.column()
+            },
+            statement: statement.map(|statement| statement.to_string()),
+            message,
+        }
+    }
+
+    /// Parses an element of the source text.
+    fn parse<T: serde::Deserialize<'a>>(
+        &self,
+        raw: &'a RawValue,
+        statement: Option<&str>,
+    ) -> Result<T, PolicyDiagnostic> {
+        serde_json::from_str::<T>(raw.get())
+            .map_err(|error| self.at_error(self.offset(raw), statement, &error))
+    }
+
+    /// Parses an element that must be a JSON object, keeping its elements borrowed.
+    fn parse_object(
+        &self,
+        raw: &'a RawValue,
+        statement: Option<&str>,
+        message: impl FnOnce() -> String,
+    ) -> Result<RawObject<'a>, PolicyDiagnostic> {
+        if !raw.get().starts_with('{') {
+            return Err(self.at(raw, statement, message()));
+        }
+        self.parse::<RawObject<'a>>(raw, statement)
+    }
+
+    /// Parses the elements of a JSON object that refer to known policy elements, ignoring
+    /// case, keyed by their spelling in the IAM policy grammar.
+    fn elements(
+        &self,
+        object: &RawObject<'a>,
+        known: &[&'static str],
+        statement: Option<&str>,
+    ) -> Result<Vec<(&'static str, &'a RawValue)>, PolicyDiagnostic> {
+        let mut elements: Vec<(&'static str, &'a RawValue)> = Vec::new();
+        for (key, raw) in object {
+            let Some(element) = canonical_element(key, known) else {
+                continue;
+            };
+            if elements.iter().any(|(seen, _)| *seen == element) {
+                return Err(self.at(raw, statement, format!("duplicate {} element", element)));
+            }
+            elements.push((element, *raw));
+        }
+        Ok(elements)
+    }
+
+    /// Checks an element holding a string or an array of strings.
+    fn check_strings(
+        &self,
+        element: &str,
+        raw: &'a RawValue,
+        statement: Option<&str>,
+    ) -> Result<(), PolicyDiagnostic> {
+        let message = || format!("{} must be a string or an array of strings", element);
+        let values = if raw.get().starts_with('[') {
+            self.parse::<Vec<&'a RawValue>>(raw, statement)?
+        } else {
+            vec![raw]
+        };
+        for value in values {
+            if !value.get().starts_with('"') {
+                return Err(self.at(value, statement, message()));
+            }
+            self.parse::<String>(value, statement)?;
+        }
+        Ok(())
+    }
+
+    /// Checks a `Principal` or `NotPrincipal` element.
+    fn check_principal(
+        &self,
+        element: &str,
+        raw: &'a RawValue,
+        statement: Option<&str>,
+    ) -> Result<(), PolicyDiagnostic> {
+        if raw.get().starts_with('"') {
+            let principal = self.parse::<String>(raw, statement)?;
+            if principal != "*" {
+                return Err(self.at(
+                    raw,
+                    statement,
+                    format!(
+                        "{} must be \"*\" or an object of principals, found \"{}\"",
+                        element, principal
+                    ),
+                ));
+            }
+            return Ok(());
+        }
+        let principals = self.parse_object(raw, statement, || {
+            format!("{} must be \"*\" or an object of principals", element)
+        })?;
+        for (principal_type, principals) in principals {
+            self.check_strings(
+                &format!("{} {}", element, principal_type),
+                principals,
+                statement,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Checks a `Condition` element.
+    fn check_condition(
+        &self,
+        raw: &'a RawValue,
+        statement: Option<&str>,
+    ) -> Result<(), PolicyDiagnostic> {
+        let operators = self.parse_object(raw, statement, || {
+            "Condition must be an object of condition operators".to_string()
+        })?;
+        for (operator, keys) in operators {
+            let keys = self.parse_object(keys, statement, || {
+                format!(
+                    "condition operator {} must be an object of condition keys",
+                    operator
+                )
+            })?;
+            for (key, values) in keys {
+                let values = if values.get().starts_with('[') {
+                    self.parse::<Vec<&'a RawValue>>(values, statement)?
+                } else {
+                    vec![values]
+                };
+                for value in values {
+                    if !matches!(
+                        self.parse::<serde_json::Value>(value, statement)?,
+                        serde_json::Value::String(_)
+                            | serde_json::Value::Bool(_)
+                            | serde_json::Value::Number(_)
+                    ) {
+                        return Err(self.at(
+                            value,
+                            statement,
+                            format!(
+                                "condition key {} must have string, boolean or numeric values",
+                                key
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a statement of the policy document.
+    fn check_statement(
+        &self,
+        statement_index: usize,
+        raw: &'a RawValue,
+    ) -> Result<(), PolicyDiagnostic> {
+        let index_label = crate::expansion::statement_label(statement_index, None);
+        let object = self.parse_object(raw, Some(&index_label), || {
+            "a statement must be an object".to_string()
+        })?;
+        let elements = self.elements(&object, STATEMENT_ELEMENTS, Some(&index_label))?;
+
+        let sid = match elements.iter().find(|(element, _)| *element == "Sid") {
+            Some((_, sid)) => Some(self.parse::<String>(sid, Some(&index_label))?),
+            None => None,
+        };
+        let label = crate::expansion::statement_label(statement_index, sid.as_deref());
+        let statement = Some(label.as_str());
+        let element = |name: &str| {
+            elements
+                .iter()
+                .find(|(element, _)| *element == name)
+                .map(|(_, raw)| *raw)
+        };
+
+        for (element, raw) in &elements {
+            match *element {
+                "Effect" => {
+                    let effect = self.parse::<String>(raw, statement)?;
+                    if effect != "Allow" && effect != "Deny" {
+                        return Err(self.at(
+                            raw,
+                            statement,
+                            format!("Effect must be \"Allow\" or \"Deny\", found \"{}\"", effect),
+                        ));
+                    }
+                }
+                "Principal" | "NotPrincipal" => self.check_principal(element, raw, statement)?,
+                "Action" | "NotAction" | "Resource" | "NotResource" => {
+                    self.check_strings(element, raw, statement)?
+                }
+                "Condition" => self.check_condition(raw, statement)?,
+                _ => {}
+            }
+        }
+
+        if element("Effect").is_none() {
+            return Err(self.at(raw, statement, "missing Effect element".to_string()));
+        }
+        for (element_name, not_element_name) in [
+            ("Principal", "NotPrincipal"),
+            ("Action", "NotAction"),
+            ("Resource", "NotResource"),
+        ] {
+            if let (Some(_), Some(not_element)) = (element(element_name), element(not_element_name))
+            {
+                return Err(self.at(
+                    not_element,
+                    statement,
+                    format!(
+                        "{} and {} cannot be used in the same statement",
+                        element_name, not_element_name
+                    ),
+                ));
+            }
+        }
+        if element("Action").is_none() && element("NotAction").is_none() {
+            return Err(self.at(
+                raw,
+                statement,
+                "missing Action or NotAction element".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the policy document.
+    fn check_document(&self) -> Result<(), PolicyDiagnostic> {
+        let document = serde_json::from_str::<RawObject<'a>>(self.source)
+            .map_err(|error| self.at_error(0, None, &error))?;
+        let elements = self.elements(&document, DOCUMENT_ELEMENTS, None)?;
+
+        let mut statements = None;
+        for (element, raw) in elements {
+            match element {
+                "Version" => {
+                    let version = self.parse::<String>(raw, None)?;
+                    if !POLICY_VERSIONS.contains(&version.as_str()) {
+                        return Err(self.at(
+                            raw,
+                            None,
+                            format!(
+                                "Version must be one of {}, found \"{}\"",
+                                POLICY_VERSIONS.join(", "),
+                                version
+                            ),
+                        ));
+                    }
+                }
+                "Id" => {
+                    self.parse::<String>(raw, None)?;
+                }
+                _ => statements = Some(raw),
+            }
+        }
+
+        let Some(statements) = statements else {
+            let start = self.source.len() - self.source.trim_start().len();
+            return Err(self.at_offset(start, None, "missing Statement element".to_string()));
+        };
+        let statements = match statements.get().as_bytes().first() {
+            Some(b'[') => self.parse::<Vec<&'a RawValue>>(statements, None)?,
+            Some(b'{') => vec![statements],
+            _ => {
+                return Err(self.at(
+                    statements,
+                    None,
+                    "Statement must be a statement object or an array of statements".to_string(),
+                ));
+            }
+        };
+        if statements.is_empty() {
+            let start = self.source.len() - self.source.trim_start().len();
+            return Err(self.at_offset(
+                start,
+                None,
+                "Statement must contain at least one statement".to_string(),
+            ));
+        }
+        for (statement_index, statement) in statements.into_iter().enumerate() {
+            self.check_statement(statement_index, statement)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a policy document, reporting documents IAM would reject with located
+/// diagnostics.
+///
+/// The document is checked element by element against its source text before it is
+/// deserialized: syntax errors, malformed elements, `Effect` values other than "Allow" and
+/// "Deny", unknown `Version` values, statements without `Effect` or without `Action` and
+/// `NotAction`, and statements combining an element with its negated form are reported with
+/// the line and column of the offending element and the label of its statement.
+///
+/// # Arguments
+///
+/// * `policy_json` - The source text of the policy document.
+///
+/// # Returns
+///
+/// A `Result` containing the parsed policy document.
+///
+/// # Errors
+///
+/// Returns a `PolicyDiagnostic` locating the first problem found in the document.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::policy::parse_policy;
+///
+/// let policy = parse_policy(r#"{ "Statement": { "Effect": "Allow", "Action": "s3:*" } }"#).unwrap();
+/// assert_eq!(policy.statement.len(), 1);
+///
+/// let diagnostic = parse_policy(
+///     "{\n  \"Version\": \"2012-10-17\",\n  \"Statement\": [\n    { \"Sid\": \"Read\", \"Effect\": \"Permit\", \"Action\": \"s3:Get*\" }\n  ]\n}",
+/// )
+/// .unwrap_err();
+/// assert_eq!((diagnostic.line, diagnostic.column), (4, 32));
+/// assert_eq!(
+///     diagnostic.to_string(),
+///     "line 4, column 32: Statement 0 (Sid: Read): Effect must be \"Allow\" or \"Deny\", found \"Permit\""
+/// );
+/// ```
+pub fn parse_policy(policy_json: &str) -> Result<AWSPolicyDocument, PolicyDiagnostic> {
+    let locator = Locator {
+        source: policy_json,
+    };
+    locator.check_document()?;
+    serde_json::from_str::<AWSPolicyDocument>(policy_json)
+        .map_err(|error| locator.at_error(0, None, &error))
+}
+
+/// Parses a YAML policy document.
+///
+/// Syntax errors and malformed values are reported with the line and column of the
+/// offending YAML node. The document is then checked like a JSON document (see
+/// [`parse_policy`]), but the problems found by these checks cannot be located in the YAML
+/// source and are only reported with the label of their statement.
+///
+/// # Arguments
+///
+/// * `policy_yaml` - The source text of the policy document.
+///
+/// # Returns
+///
+/// A `Result` containing the parsed policy document.
+///
+/// # Errors
+///
+/// Returns a `PolicyDiagnostic` describing the first problem found in the document.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::policy::parse_yaml_policy;
+///
+/// let policy = parse_yaml_policy(
+///     "Version: 2012-10-17\nStatement:\n  - Effect: Allow\n    Action: s3:Get*\n    Resource: '*'\n",
+/// )
+/// .unwrap();
+/// assert_eq!(policy.version.as_deref(), Some("2012-10-17"));
+///
+/// let diagnostic = parse_yaml_policy("Statement:\n  - Effect: Allow\n    Action: [s3:Get*\n").unwrap_err();
+/// assert_eq!(diagnostic.line, 4);
+///
+/// let diagnostic = parse_yaml_policy("Statement:\n  - Sid: Read\n    Effect: Permit\n    Action: s3:Get*\n").unwrap_err();
+/// assert_eq!(
+///     diagnostic.to_string(),
+///     "Statement 0 (Sid: Read): Effect must be \"Allow\" or \"Deny\", found \"Permit\""
+/// );
+/// ```
+pub fn parse_yaml_policy(policy_yaml: &str) -> Result<AWSPolicyDocument, PolicyDiagnostic> {
+    let yaml_diagnostic = |error: serde_norway::Error| {
+        let (line, column) = error
+            .location()
+            .map_or((0, 0), |location| (location.line(), location.column()));
+        let mut message = error.to_string();
+        if let Some(position) = message.rfind(" at line ") {
+            message.truncate(position);
+        }
+        PolicyDiagnostic {
+            line,
+            column,
+            statement: None,
+            message,
+        }
+    };
+    let value =
+        serde_norway::from_str::<serde_json::Value>(policy_yaml).map_err(yaml_diagnostic)?;
+    parse_policy_value(&value)
+}
+
+/// Parses a policy document that is already a JSON value, such as a document converted
+/// from YAML or embedded in a CloudFormation template.
+///
+/// The document is checked like a JSON document (see [`parse_policy`]), but the problems
+/// found cannot be located in a source text and are only reported with the label of their
+/// statement.
+///
+/// # Arguments
+///
+/// * `value` - The policy document.
+///
+/// # Returns
+///
+/// A `Result` containing the parsed policy document.
+///
+/// # Errors
+///
+/// Returns a `PolicyDiagnostic` describing the first problem found in the document.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::policy::parse_policy_value;
+///
+/// let document = serde_json::json!({ "Statement": [{ "Effect": "Allow" }] });
+/// assert_eq!(
+///     parse_policy_value(&document).unwrap_err().to_string(),
+///     "Statement 0: missing Action or NotAction element"
+/// );
+/// ```
+pub fn parse_policy_value(
+    value: &serde_json::Value,
+) -> Result<AWSPolicyDocument, PolicyDiagnostic> {
+    parse_policy(&value.to_string()).map_err(|diagnostic| PolicyDiagnostic {
+        line: 0,
+        column: 0,
+        ..diagnostic
+    })
+}
+
+/// The serialization format of a policy document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PolicyFormat {
+    /// A JSON document.
+    Json,
+
+    /// A YAML document.
+    Yaml,
+}
+
+impl PolicyFormat {
+    /// Detects the format of a file from its extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file.
+    ///
+    /// # Returns
+    ///
+    /// `Some(PolicyFormat::Yaml)` for `.yaml` and `.yml` files, `Some(PolicyFormat::Json)`
+    /// for `.json` files, and `None` for any other file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::policy::PolicyFormat;
+    ///
+    /// assert_eq!(PolicyFormat::from_extension("policies/s3.YML"), Some(PolicyFormat::Yaml));
+    /// assert_eq!(PolicyFormat::from_extension("policy.json"), Some(PolicyFormat::Json));
+    /// assert_eq!(PolicyFormat::from_extension("policy.txt"), None);
+    /// ```
+    pub fn from_extension(path: &str) -> Option<Self> {
+        let extension = std::path::Path::new(path)
+            .extension()?
+            .to_string_lossy()
+            .to_lowercase();
+        match extension.as_str() {
+            "yaml" | "yml" => Some(PolicyFormat::Yaml),
+            "json" => Some(PolicyFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Serializes a value in this format, pretty-printing JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to serialize.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the serialized value, or an error if it cannot be serialized.
+    pub fn render<T: serde::Serialize>(
+        &self,
+        value: &T,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(match self {
+            PolicyFormat::Json => serde_json::to_string_pretty(value)?,
+            PolicyFormat::Yaml => serde_norway::to_string(value)?,
+        })
+    }
+}
+
+/// A policy document read from a file, with what is needed to write it back in its own
+/// format.
+#[derive(Debug, Clone)]
+pub struct PolicyFile {
+    /// The policy document.
+    pub document: AWSPolicyDocument,
+
+    /// The format the document was written in.
+    pub format: PolicyFormat,
+
+    /// The comment lines heading a YAML document (e.g., an ownership notice), kept when it
+    /// is written back as YAML. Empty for JSON documents.
+    pub header: String,
+
+    /// The number of lines holding a comment within a YAML document, including comments
+    /// trailing a value, which cannot be kept when it is written back.
+    pub inner_comments: usize,
+}
+
+/// Returns `true` if a YAML line holds a comment, on its own or trailing a value.
+///
+/// A `#` starts a comment at the start of the line or after a space, outside of quotes.
+fn has_yaml_comment(line: &str) -> bool {
+    let mut quote: Option<char> = None;
+    let mut previous = ' ';
+    for ch in line.chars() {
+        match (quote, ch) {
+            (None, '#') if previous.is_whitespace() => return true,
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(open), _) if ch == open => quote = None,
+            _ => {}
+        }
+        previous = ch;
+    }
+    false
+}
+
+impl PolicyFile {
+    /// Parses a policy document in the given format.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source text of the policy document.
+    /// * `format` - The format of the document.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed policy file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PolicyDiagnostic` describing the first problem found in the document
+    /// (see [`parse_policy`] and [`parse_yaml_policy`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::policy::{PolicyFile, PolicyFormat};
+    ///
+    /// let source = "# Owned by the platform team\n\nStatement:\n  # Read-only access\n  Effect: Allow\n  Action: s3:Get*\n";
+    /// let policy = PolicyFile::parse(source, PolicyFormat::Yaml).unwrap();
+    /// assert_eq!(policy.inner_comments, 1);
+    /// assert_eq!(
+    ///     policy.render(PolicyFormat::Yaml).unwrap(),
+    ///     "# Owned by the platform team\n\nStatement:\n  Effect: Allow\n  Action: s3:Get*\n"
+    /// );
+    /// ```
+    pub fn parse(source: &str, format: PolicyFormat) -> Result<Self, PolicyDiagnostic> {
+        match format {
+            PolicyFormat::Json => Ok(PolicyFile {
+                document: parse_policy(source)?,
+                format,
+                header: String::new(),
+                inner_comments: 0,
+            }),
+            PolicyFormat::Yaml => {
+                let document = parse_yaml_policy(source)?;
+                let lines = source.lines().collect::<Vec<&str>>();
+                let header_length = lines
+                    .iter()
+                    .take_while(|line| line.trim().is_empty() || line.trim_start().starts_with('#'))
+                    .count();
+                let header = lines[..header_length]
+                    .iter()
+                    .fold(String::new(), |header, line| header + line + "\n");
+                let inner_comments = lines[header_length..]
+                    .iter()
+                    .filter(|line| has_yaml_comment(line))
+                    .count();
+                Ok(PolicyFile {
+                    document,
+                    format,
+                    header: if header.trim().is_empty() {
+                        String::new()
+                    } else {
+                        header
+                    },
+                    inner_comments,
+                })
+            }
+        }
+    }
+
+    /// Serializes the policy document in the given format, heading a YAML document with the
+    /// comments heading the original one.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format to write the document in.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the serialized document, or an error if it cannot be serialized.
+    pub fn render(&self, format: PolicyFormat) -> Result<String, Box<dyn std::error::Error>> {
+        let document = format.render(&self.document)?;
+        match format {
+            PolicyFormat::Json => Ok(document),
+            PolicyFormat::Yaml => Ok(format!("{}{}", self.header, document)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_own_line_and_trailing_yaml_comments() {
+        let source = "# Owner\nStatement:\n  # Read-only\n  Effect: Allow # trailing\n  Action: 's3:Get#*'\n  Resource: \"arn:aws:s3:::bucket #1\"\n";
+        let policy = PolicyFile::parse(source, PolicyFormat::Yaml).unwrap();
+        assert_eq!(policy.header, "# Owner\n");
+        assert_eq!(policy.inner_comments, 2);
+    }
+}
@@ -0,0 +1,233 @@
+//! Loader for the policy_sentry IAM definition database.
+//!
+//! [policy_sentry](https://github.com/salesforce/policy_sentry) ships the IAM documentation
+//! scraped into a single `iam-definition.json` file, keyed by service prefix, with the
+//! access level, resource types, condition keys and dependent actions of every action.
+//! This module normalizes that database into the [`AwsService`](crate::types::AwsService) model.
+
+use std::collections::HashMap;
+
+/// A service of the policy_sentry IAM definition database.
+#[derive(Debug, serde::Deserialize)]
+struct SentryService {
+    /// The full name of the service (e.g., "Amazon S3").
+    service_name: String,
+
+    /// The service prefix (e.g., "s3").
+    prefix: String,
+
+    /// The actions of the service, keyed by name without the service prefix.
+    #[serde(default)]
+    privileges: HashMap<String, SentryPrivilege>,
+
+    /// The resource types of the service, keyed by name.
+    #[serde(default)]
+    resources: HashMap<String, SentryResource>,
+}
+
+/// An action of a policy_sentry service.
+#[derive(Debug, serde::Deserialize)]
+struct SentryPrivilege {
+    /// The action name without the service prefix (e.g., "GetObject").
+    privilege: String,
+
+    /// The access level of the action (e.g., "Read", "Permissions management").
+    access_level: String,
+
+    /// The rows of the action table, keyed by resource type.
+    ///
+    /// Required resource types end with `*`; the empty key holds the row without resource type.
+    #[serde(default)]
+    resource_types: HashMap<String, SentryResourceTypeRow>,
+}
+
+/// A row of the action table of a policy_sentry action.
+#[derive(Debug, serde::Deserialize)]
+struct SentryResourceTypeRow {
+    /// The condition keys listed in the row.
+    #[serde(default)]
+    condition_keys: Vec<String>,
+
+    /// The dependent actions listed in the row.
+    #[serde(default)]
+    dependent_actions: Vec<String>,
+}
+
+/// A resource type of a policy_sentry service.
+#[derive(Debug, serde::Deserialize)]
+struct SentryResource {
+    /// The ARN format of the resource type.
+    #[serde(default)]
+    arn: Option<String>,
+
+    /// The condition keys that apply to the resource type.
+    #[serde(default)]
+    condition_keys: Vec<String>,
+}
+
+/// Appends the values not already present to a list, preserving order.
+fn extend_unique(values: &mut Vec<String>, additional: &[String]) {
+    for value in additional {
+        if !values.contains(value) {
+            values.push(value.clone());
+        }
+    }
+}
+
+impl SentryService {
+    /// Normalizes the policy_sentry service into an `AwsService`.
+    fn into_service(self) -> crate::types::AwsService {
+        let mut privileges = self
+            .privileges
+            .into_values()
+            .collect::<Vec<SentryPrivilege>>();
+        privileges.sort_by(|a, b| a.privilege.cmp(&b.privilege));
+
+        let actions = privileges
+            .into_iter()
+            .map(|privilege| {
+                let mut rows = privilege
+                    .resource_types
+                    .into_iter()
+                    .collect::<Vec<(String, SentryResourceTypeRow)>>();
+                rows.sort_by(|a, b| a.0.cmp(&b.0));
+
+                let mut resource_types: Vec<crate::types::AwsResourceType> = Vec::new();
+                let mut condition_keys: Vec<String> = Vec::new();
+                let mut dependent_actions: Vec<String> = Vec::new();
+                for (resource_type, row) in rows {
+                    extend_unique(&mut condition_keys, &row.condition_keys);
+                    extend_unique(&mut dependent_actions, &row.dependent_actions);
+                    if resource_type.is_empty() {
+                        continue;
+                    }
+
+                    let name = resource_type.trim_end_matches('*').to_string();
+                    let resource = self.resources.get(&name);
+                    resource_types.push(crate::types::AwsResourceType {
+                        arn_formats: resource
+                            .and_then(|resource| resource.arn.clone())
+                            .into_iter()
+                            .collect::<Vec<String>>(),
+                        condition_keys: resource
+                            .map(|resource| resource.condition_keys.clone())
+                            .unwrap_or_default(),
+                        required: Some(resource_type.ends_with('*')),
+                        name,
+                    });
+                }
+
+                crate::types::AwsAction {
+                    name: format!("{}:{}", self.prefix, privilege.privilege),
+                    action_type: privilege.access_level,
+                    resource_types,
+                    condition_keys,
+                    dependent_actions,
+                }
+            })
+            .collect::<Vec<crate::types::AwsAction>>();
+
+        crate::types::AwsService {
+            name: self.service_name,
+            prefix: self.prefix,
+            actions,
+        }
+    }
+}
+
+/// Parses a policy_sentry IAM definition database into AWS services.
+///
+/// # Arguments
+///
+/// * `definition_json` - The contents of the `iam-definition.json` file.
+///
+/// # Returns
+///
+/// A `Result` containing the normalized services, sorted by prefix, or an error if the JSON
+/// is invalid.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::policy_sentry::parse_iam_definition;
+///
+/// let services = parse_iam_definition(r#"{
+///     "iam": {
+///         "service_name": "AWS Identity and Access Management (IAM)",
+///         "prefix": "iam",
+///         "privileges": {
+///             "PassRole": {
+///                 "privilege": "PassRole",
+///                 "access_level": "Write",
+///                 "resource_types": {
+///                     "role*": {
+///                         "condition_keys": ["iam:PassedToService"],
+///                         "dependent_actions": []
+///                     }
+///                 }
+///             }
+///         },
+///         "resources": {
+///             "role": { "arn": "arn:${Partition}:iam::${Account}:role/${RoleNameWithPath}" }
+///         }
+///     }
+/// }"#)
+/// .unwrap();
+///
+/// let action = &services[0].actions[0];
+/// assert_eq!(action.name, "iam:PassRole");
+/// assert_eq!(action.action_type, "Write");
+/// assert_eq!(action.resource_types[0].name, "role");
+/// assert_eq!(action.resource_types[0].required, Some(true));
+/// assert_eq!(action.condition_keys, vec!["iam:PassedToService"]);
+/// ```
+pub fn parse_iam_definition(
+    definition_json: &str,
+) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>> {
+    let mut services = serde_json::from_str::<HashMap<String, SentryService>>(definition_json)?
+        .into_values()
+        .map(SentryService::into_service)
+        .collect::<Vec<crate::types::AwsService>>();
+    services.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+    Ok(services)
+}
+
+/// Loads a policy_sentry IAM definition database from a local file.
+///
+/// # Arguments
+///
+/// * `path` - The path to the `iam-definition.json` file.
+///
+/// # Returns
+///
+/// A `Result` containing the normalized services, or an error if the file cannot be read
+/// or parsed.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use aws_iam_expansion::policy_sentry::load_iam_definition;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let services = load_iam_definition("iam-definition.json")?;
+/// println!("Loaded {} services", services.len());
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_iam_definition(
+    path: &str,
+) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>> {
+    let path = shellexpand::tilde(path).to_string();
+    let definition_json = std::fs::read_to_string(&path).map_err(|error| {
+        format!(
+            "Could not read policy_sentry IAM definition '{}': {}",
+            path, error
+        )
+    })?;
+    parse_iam_definition(&definition_json).map_err(|error| {
+        format!(
+            "Could not parse policy_sentry IAM definition '{}': {}",
+            path, error
+        )
+        .into()
+    })
+}
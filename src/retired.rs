@@ -0,0 +1,360 @@
+//! Detection of deprecated, renamed and removed actions.
+//!
+//! AWS occasionally retires actions, either dropping them or replacing them with finer
+//! grained ones (e.g., the `aws-portal` billing actions replaced by the `billing`,
+//! `account`, `payments` and related services). Policies written before such a change keep
+//! referencing the old names, which no longer match anything in the current catalog.
+//!
+//! This module keeps an [`ActionHistory`] of the actions seen in earlier catalogs (the
+//! caches archived by `update-cache`, the embedded catalog snapshot and any catalog given
+//! explicitly), and reports the actions of a pattern that are missing from the current
+//! catalog, together with the replacements listed in a built-in table of known renames.
+
+use std::collections::BTreeMap;
+
+/// The known replacements of retired actions, keyed by retired action name.
+///
+/// The `aws-portal` and `purchase-orders` mappings follow the AWS guide to migrating to the
+/// fine-grained Billing, Cost Management and Account actions.
+const KNOWN_REPLACEMENTS: &[(&str, &[&str])] = &[
+    (
+        "aws-portal:ModifyAccount",
+        &[
+            "account:CloseAccount",
+            "account:DeleteAlternateContact",
+            "account:PutAlternateContact",
+            "account:PutChallengeQuestions",
+            "account:PutContactInformation",
+            "billing:PutContractInformation",
+            "billing:UpdateIAMAccessPreference",
+        ],
+    ),
+    (
+        "aws-portal:ModifyBilling",
+        &[
+            "billing:PutContractInformation",
+            "billing:RedeemCredits",
+            "billing:UpdateBillingPreferences",
+            "ce:UpdatePreferences",
+            "cur:PutClassicReportPreferences",
+            "freetier:PutFreeTierAlertPreference",
+            "invoicing:PutInvoiceEmailDeliveryPreferences",
+            "payments:CreatePaymentInstrument",
+            "payments:DeletePaymentInstrument",
+            "payments:MakePayment",
+            "payments:UpdatePaymentPreferences",
+            "tax:BatchPutTaxRegistration",
+            "tax:DeleteTaxRegistration",
+            "tax:PutTaxInheritance",
+        ],
+    ),
+    (
+        "aws-portal:ModifyPaymentMethods",
+        &[
+            "account:GetAccountInformation",
+            "invoicing:GetInvoicePDF",
+            "payments:CreatePaymentInstrument",
+            "payments:DeletePaymentInstrument",
+            "payments:GetPaymentInstrument",
+            "payments:GetPaymentStatus",
+            "payments:ListPaymentPreferences",
+            "payments:MakePayment",
+            "payments:UpdatePaymentPreferences",
+        ],
+    ),
+    (
+        "aws-portal:ViewAccount",
+        &[
+            "account:GetAccountInformation",
+            "account:GetAlternateContact",
+            "account:GetChallengeQuestions",
+            "account:GetContactInformation",
+            "billing:GetContractInformation",
+            "billing:GetIAMAccessPreference",
+            "billing:GetSellerOfRecord",
+            "payments:ListPaymentPreferences",
+        ],
+    ),
+    (
+        "aws-portal:ViewBilling",
+        &[
+            "billing:GetBillingData",
+            "billing:GetBillingDetails",
+            "billing:GetBillingNotifications",
+            "billing:GetBillingPreferences",
+            "billing:GetContractInformation",
+            "billing:GetCredits",
+            "billing:GetIAMAccessPreference",
+            "billing:GetSellerOfRecord",
+            "billing:ListBillingViews",
+            "ce:GetPreferences",
+            "consolidatedbilling:GetAccountBillingRole",
+            "consolidatedbilling:ListLinkedAccounts",
+            "cur:GetClassicReport",
+            "cur:GetClassicReportPreferences",
+            "freetier:GetFreeTierAlertPreference",
+            "freetier:GetFreeTierUsage",
+            "invoicing:GetInvoiceEmailDeliveryPreferences",
+            "invoicing:GetInvoicePDF",
+            "invoicing:ListInvoiceSummaries",
+            "payments:GetPaymentInstrument",
+            "payments:GetPaymentStatus",
+            "payments:ListPaymentPreferences",
+            "tax:GetTaxInheritance",
+            "tax:GetTaxRegistrationDocument",
+            "tax:ListTaxRegistrations",
+        ],
+    ),
+    (
+        "aws-portal:ViewPaymentMethods",
+        &[
+            "account:GetAccountInformation",
+            "invoicing:GetInvoicePDF",
+            "payments:GetPaymentInstrument",
+            "payments:GetPaymentStatus",
+            "payments:ListPaymentPreferences",
+        ],
+    ),
+    ("aws-portal:ViewUsage", &["cur:GetUsageReport"]),
+    (
+        "purchase-orders:ModifyPurchaseOrders",
+        &[
+            "purchase-orders:AddPurchaseOrder",
+            "purchase-orders:DeletePurchaseOrder",
+            "purchase-orders:UpdatePurchaseOrder",
+            "purchase-orders:UpdatePurchaseOrderStatus",
+        ],
+    ),
+    (
+        "purchase-orders:ViewPurchaseOrders",
+        &[
+            "purchase-orders:GetPurchaseOrder",
+            "purchase-orders:ListPurchaseOrderInvoices",
+            "purchase-orders:ListPurchaseOrders",
+        ],
+    ),
+];
+
+/// Returns the known replacements of a retired action, ignoring case.
+///
+/// # Arguments
+///
+/// * `action` - The full action name (e.g., "aws-portal:ViewUsage").
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::retired::known_replacements;
+///
+/// assert_eq!(known_replacements("aws-portal:viewusage"), Some(&["cur:GetUsageReport"][..]));
+/// assert_eq!(known_replacements("s3:GetObject"), None);
+/// ```
+pub fn known_replacements(action: &str) -> Option<&'static [&'static str]> {
+    KNOWN_REPLACEMENTS
+        .iter()
+        .find(|(retired, _)| retired.eq_ignore_ascii_case(action))
+        .map(|(_, replacements)| *replacements)
+}
+
+/// Whether a retired action is still listed in the current catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetirementStatus {
+    /// The action is still listed, but has known replacements.
+    Deprecated,
+
+    /// The action is missing from the current catalog.
+    Removed,
+}
+
+/// A deprecated or removed action referenced by a policy.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RetiredAction {
+    /// The name of the action, as last seen in a catalog or in the table of known renames.
+    pub action: String,
+
+    /// Whether the action is deprecated or removed.
+    pub status: RetirementStatus,
+
+    /// The label of the latest earlier catalog listing the action (e.g., "2024-03-01"), if any.
+    pub last_seen: Option<String>,
+
+    /// The known replacements of the action, if any.
+    pub replacements: Vec<String>,
+}
+
+impl std::fmt::Display for RetiredAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            RetirementStatus::Deprecated => write!(f, "'{}' is deprecated", self.action)?,
+            RetirementStatus::Removed => {
+                write!(f, "'{}' was removed from the catalog", self.action)?
+            }
+        }
+        if let Some(last_seen) = &self.last_seen {
+            write!(f, " (last seen: {})", last_seen)?;
+        }
+        if !self.replacements.is_empty() {
+            write!(f, "; use {} instead", self.replacements.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The actions seen in earlier catalogs.
+#[derive(Debug, Default)]
+pub struct ActionHistory {
+    /// The actions, keyed by case-folded name, with their name and the label of the latest
+    /// catalog listing them.
+    actions: BTreeMap<String, (String, String)>,
+}
+
+impl ActionHistory {
+    /// Loads the actions of the caches archived by `update-cache` and of the embedded
+    /// catalog snapshot, if any.
+    ///
+    /// Archived caches that cannot be parsed are skipped with a warning, and the caches
+    /// archived with their source are labelled with it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the history, or an error if the archived caches cannot be read
+    /// or the embedded snapshot cannot be parsed.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let mut history = Self::default();
+        if let Some(snapshot) = crate::utils::embedded_snapshot() {
+            history.add_services(
+                &format!("embedded snapshot of {}", snapshot.date),
+                &crate::source::parse_catalog_json(snapshot.json)?,
+            );
+        }
+        for archive in crate::utils::read_iam_actions_history()? {
+            let services = match crate::source::parse_catalog_json(&archive.json) {
+                Ok(services) => services,
+                Err(error) => {
+                    eprintln!(
+                        "[!] Skipping archived AWS IAM actions cache of {}: {}",
+                        archive.date, error
+                    );
+                    continue;
+                }
+            };
+            let label = match &archive.source {
+                Some(source) => format!("cache of {} from {}", archive.date, source),
+                None => format!("cache of {}", archive.date),
+            };
+            history.add_services(&label, &services);
+        }
+        Ok(history)
+    }
+
+    /// Records the actions of an earlier catalog.
+    ///
+    /// Catalogs are expected to be added oldest first: the label of an action already
+    /// recorded is replaced with the new one.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label identifying the catalog in reports (e.g., its date).
+    /// * `services` - The services of the catalog.
+    pub fn add_services(&mut self, label: &str, services: &[crate::types::AwsService]) {
+        for action in services.iter().flat_map(|service| service.actions.iter()) {
+            self.actions.insert(
+                action.name.to_lowercase(),
+                (action.name.clone(), label.to_string()),
+            );
+        }
+    }
+
+    /// Finds the deprecated and removed actions matching an IAM action pattern.
+    ///
+    /// An action is removed when it was seen in an earlier catalog, or is listed in the
+    /// table of known renames, but is missing from the current catalog. An action of the
+    /// table of known renames still listed in the current catalog is deprecated.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The action pattern as written in a policy (e.g., "aws-portal:View*").
+    /// * `current` - The current catalog.
+    ///
+    /// # Returns
+    ///
+    /// The matching retired actions, sorted by name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aws_iam_expansion::{
+    ///     catalog::Catalog,
+    ///     retired::{ActionHistory, RetirementStatus},
+    ///     types::AwsService,
+    /// };
+    ///
+    /// let services = |actions: serde_json::Value| {
+    ///     serde_json::from_value::<Vec<AwsService>>(serde_json::json!([{
+    ///         "service": "Amazon S3",
+    ///         "servicePrefix": "s3",
+    ///         "actions": actions
+    ///     }])).unwrap()
+    /// };
+    /// let mut history = ActionHistory::default();
+    /// history.add_services("2023-01-01", &services(serde_json::json!([
+    ///     { "action": "s3:GetObject", "type": "Read" },
+    ///     { "action": "s3:GetLegacyThing", "type": "Read" }
+    /// ])));
+    /// let current = Catalog::new(services(serde_json::json!([
+    ///     { "action": "s3:GetObject", "type": "Read" }
+    /// ])));
+    ///
+    /// let retired = history.retired("s3:GetLegacy*", &current);
+    /// assert_eq!(retired[0].action, "s3:GetLegacyThing");
+    /// assert_eq!(retired[0].status, RetirementStatus::Removed);
+    /// assert_eq!(retired[0].last_seen.as_deref(), Some("2023-01-01"));
+    /// assert!(history.retired("s3:GetObject", &current).is_empty());
+    ///
+    /// let retired = history.retired("aws-portal:ViewUsage", &current);
+    /// assert_eq!(retired[0].replacements, vec!["cur:GetUsageReport"]);
+    /// ```
+    pub fn retired(&self, pattern: &str, current: &crate::catalog::Catalog) -> Vec<RetiredAction> {
+        let mut retired: BTreeMap<String, RetiredAction> = BTreeMap::new();
+
+        for (folded, (action, last_seen)) in &self.actions {
+            if current.action(action).is_none() && crate::pattern::wildcard_match(pattern, action) {
+                retired.insert(
+                    folded.clone(),
+                    RetiredAction {
+                        action: action.clone(),
+                        status: RetirementStatus::Removed,
+                        last_seen: Some(last_seen.clone()),
+                        replacements: Vec::new(),
+                    },
+                );
+            }
+        }
+
+        for (action, replacements) in KNOWN_REPLACEMENTS {
+            if !crate::pattern::wildcard_match(pattern, action) {
+                continue;
+            }
+            let status = if current.action(action).is_some() {
+                RetirementStatus::Deprecated
+            } else {
+                RetirementStatus::Removed
+            };
+            let entry = retired
+                .entry(action.to_lowercase())
+                .or_insert_with(|| RetiredAction {
+                    action: action.to_string(),
+                    status,
+                    last_seen: None,
+                    replacements: Vec::new(),
+                });
+            entry.replacements = replacements
+                .iter()
+                .map(|replacement| replacement.to_string())
+                .collect::<Vec<String>>();
+        }
+
+        retired.into_values().collect::<Vec<RetiredAction>>()
+    }
+}
@@ -0,0 +1,257 @@
+//! Loader for the AWS Service Authorization Reference JSON format.
+//!
+//! AWS publishes machine-readable service authorization data, one JSON document per
+//! service, describing every action with its access level, supported resource types
+//! (with their ARN formats) and condition keys. This module reads a local copy of these
+//! documents and normalizes them into the [`AwsService`](crate::types::AwsService) model.
+
+/// A service document of the Service Authorization Reference.
+#[derive(Debug, serde::Deserialize)]
+struct ReferenceService {
+    /// The service prefix (e.g., "s3").
+    #[serde(rename = "Name")]
+    name: String,
+
+    /// The actions of the service.
+    #[serde(rename = "Actions", default)]
+    actions: Vec<ReferenceAction>,
+
+    /// The resource types of the service.
+    #[serde(rename = "Resources", default)]
+    resources: Vec<ReferenceResource>,
+}
+
+/// An action of a Service Authorization Reference service document.
+#[derive(Debug, serde::Deserialize)]
+struct ReferenceAction {
+    /// The action name without the service prefix (e.g., "GetObject").
+    #[serde(rename = "Name")]
+    name: String,
+
+    /// The condition keys supported by the action.
+    #[serde(rename = "ActionConditionKeys", default)]
+    condition_keys: Vec<String>,
+
+    /// The annotations describing the access level of the action.
+    #[serde(rename = "Annotations", default)]
+    annotations: Option<ReferenceAnnotations>,
+
+    /// The resource types the action can be scoped to.
+    #[serde(rename = "Resources", default)]
+    resources: Vec<ReferenceActionResource>,
+
+    /// The dependent actions of the action, if the document lists them.
+    #[serde(rename = "DependentActions", default)]
+    dependent_actions: Vec<String>,
+}
+
+/// The annotations of a Service Authorization Reference action.
+#[derive(Debug, Default, serde::Deserialize)]
+struct ReferenceAnnotations {
+    /// The access level properties of the action.
+    #[serde(rename = "Properties", default)]
+    properties: ReferenceProperties,
+}
+
+/// The access level properties of a Service Authorization Reference action.
+#[derive(Debug, Default, serde::Deserialize)]
+struct ReferenceProperties {
+    #[serde(rename = "IsList", default)]
+    is_list: bool,
+
+    #[serde(rename = "IsPermissionManagement", default)]
+    is_permission_management: bool,
+
+    #[serde(rename = "IsTaggingOnly", default)]
+    is_tagging_only: bool,
+
+    #[serde(rename = "IsWrite", default)]
+    is_write: bool,
+}
+
+impl ReferenceProperties {
+    /// Maps the properties to the access level names used by the IAM documentation.
+    fn access_level(&self) -> &'static str {
+        if self.is_permission_management {
+            "Permissions management"
+        } else if self.is_tagging_only {
+            "Tagging"
+        } else if self.is_write {
+            "Write"
+        } else if self.is_list {
+            "List"
+        } else {
+            "Read"
+        }
+    }
+}
+
+/// A reference from a Service Authorization Reference action to a resource type.
+#[derive(Debug, serde::Deserialize)]
+struct ReferenceActionResource {
+    /// The name of the resource type.
+    #[serde(rename = "Name")]
+    name: String,
+}
+
+/// A resource type of a Service Authorization Reference service document.
+#[derive(Debug, serde::Deserialize)]
+struct ReferenceResource {
+    /// The name of the resource type.
+    #[serde(rename = "Name")]
+    name: String,
+
+    /// The ARN formats of the resource type.
+    #[serde(rename = "ARNFormats", default)]
+    arn_formats: Vec<String>,
+
+    /// The condition keys that apply to the resource type.
+    #[serde(rename = "ConditionKeys", default)]
+    condition_keys: Vec<String>,
+}
+
+impl ReferenceService {
+    /// Normalizes the service document into an `AwsService`.
+    ///
+    /// Service documents do not carry the full service name, so the service prefix is used.
+    fn into_service(self) -> crate::types::AwsService {
+        let resources = self.resources;
+        let actions = self
+            .actions
+            .into_iter()
+            .map(|action| crate::types::AwsAction {
+                name: format!("{}:{}", self.name, action.name),
+                action_type: action
+                    .annotations
+                    .unwrap_or_default()
+                    .properties
+                    .access_level()
+                    .to_string(),
+                resource_types: action
+                    .resources
+                    .iter()
+                    .map(|reference| {
+                        let resource = resources
+                            .iter()
+                            .find(|resource| resource.name == reference.name);
+                        crate::types::AwsResourceType {
+                            name: reference.name.clone(),
+                            arn_formats: resource
+                                .map(|resource| resource.arn_formats.clone())
+                                .unwrap_or_default(),
+                            condition_keys: resource
+                                .map(|resource| resource.condition_keys.clone())
+                                .unwrap_or_default(),
+                            required: None,
+                        }
+                    })
+                    .collect::<Vec<crate::types::AwsResourceType>>(),
+                condition_keys: action.condition_keys,
+                dependent_actions: action.dependent_actions,
+            })
+            .collect::<Vec<crate::types::AwsAction>>();
+
+        crate::types::AwsService {
+            name: self.name.clone(),
+            prefix: self.name,
+            actions,
+        }
+    }
+}
+
+/// Parses Service Authorization Reference JSON into AWS services.
+///
+/// The JSON may hold a single service document or an array of service documents.
+///
+/// # Arguments
+///
+/// * `reference_json` - The Service Authorization Reference JSON.
+///
+/// # Returns
+///
+/// A `Result` containing the normalized services, or an error if the JSON is invalid.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::service_reference::parse_service_reference;
+///
+/// let services = parse_service_reference(r#"{
+///     "Name": "s3",
+///     "Actions": [{
+///         "Name": "GetObject",
+///         "ActionConditionKeys": ["s3:ExistingObjectTag/${TagKey}"],
+///         "Annotations": { "Properties": { "IsList": false, "IsPermissionManagement": false,
+///                                           "IsTaggingOnly": false, "IsWrite": false } },
+///         "Resources": [{ "Name": "object" }]
+///     }],
+///     "Resources": [{
+///         "Name": "object",
+///         "ARNFormats": ["arn:${Partition}:s3:::${BucketName}/${ObjectName}"]
+///     }]
+/// }"#)
+/// .unwrap();
+///
+/// let action = &services[0].actions[0];
+/// assert_eq!(action.name, "s3:GetObject");
+/// assert_eq!(action.action_type, "Read");
+/// assert_eq!(action.resource_types[0].arn_formats.len(), 1);
+/// assert_eq!(action.condition_keys, vec!["s3:ExistingObjectTag/${TagKey}"]);
+/// ```
+pub fn parse_service_reference(
+    reference_json: &str,
+) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>> {
+    let documents = match serde_json::from_str::<serde_json::Value>(reference_json)? {
+        serde_json::Value::Array(documents) => documents,
+        document => vec![document],
+    };
+
+    documents
+        .into_iter()
+        .map(|document| {
+            serde_json::from_value::<ReferenceService>(document)
+                .map(ReferenceService::into_service)
+                .map_err(|error| error.into())
+        })
+        .collect()
+}
+
+/// Loads a local copy of the AWS Service Authorization Reference.
+///
+/// The path may point to a single JSON file (holding one service document or an array of
+/// them) or to a directory, in which case every `.json` file it contains is loaded.
+///
+/// # Arguments
+///
+/// * `path` - The path to the JSON file or directory.
+///
+/// # Returns
+///
+/// A `Result` containing the normalized services, or an error if a file cannot be read or parsed.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use aws_iam_expansion::service_reference::load_service_reference;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let services = load_service_reference("service-reference/")?;
+/// println!("Loaded {} services", services.len());
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_service_reference(
+    path: &str,
+) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>> {
+    let mut services = Vec::new();
+    for file in crate::source::json_files(path)? {
+        let reference_json = std::fs::read_to_string(&file)?;
+        services.extend(parse_service_reference(&reference_json).map_err(|error| {
+            format!(
+                "Could not parse service reference file '{}': {}",
+                file.display(),
+                error
+            )
+        })?);
+    }
+    Ok(services)
+}
@@ -0,0 +1,371 @@
+//! Pluggable sources for the catalog of AWS IAM services and actions.
+//!
+//! The catalog can be loaded from several places, each with its own JSON shape: the AWS
+//! IAM Actions API (awsiamactions.io), a local copy of the AWS Service Authorization
+//! Reference, the policy_sentry IAM definition database, or a plain local file or directory
+//! in the toolkit's own format. Every source implements [`CatalogSource`] and normalizes its
+//! data into the [`AwsService`](crate::types::AwsService) model, so the toolkit does not
+//! depend on a single third-party website staying up.
+//!
+//! The source is selected with the `--catalog-source` and `--catalog-path` flags, or with
+//! the configuration file at `~/.config/aws_iam_expansion/config.json`:
+//!
+//! ```json
+//! {
+//!   "catalog_source": "policy-sentry",
+//!   "catalog_path": "~/iam-definition.json"
+//! }
+//! ```
+//!
+//! Setting `"offline": true` (or passing `--offline`) keeps the AWS IAM Actions API source
+//! from reaching the network, relying on the cache or the embedded catalog snapshot.
+
+/// A source the catalog of AWS IAM services and actions can be loaded from.
+pub trait CatalogSource {
+    /// Returns a human-readable description of the source, used in messages.
+    fn name(&self) -> String;
+
+    /// Loads the catalog, normalized into AWS services.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be retrieved or parsed.
+    fn load(&self) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>>;
+
+    /// Refreshes the local cache of the source, archiving the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not cached, or if the data cannot be retrieved.
+    fn update_cache(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Err(format!(
+            "Only the AWS IAM Actions API source is cached, {} has no cache to update",
+            self.name()
+        )
+        .into())
+    }
+}
+
+/// The kinds of built-in catalog sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CatalogSourceKind {
+    /// The AWS IAM Actions API (awsiamactions.io) JSON, cached locally.
+    AwsIamActions,
+
+    /// A local copy of the AWS Service Authorization Reference JSON.
+    ServiceReference,
+
+    /// A local copy of the policy_sentry IAM definition database (`iam-definition.json`).
+    PolicySentry,
+
+    /// A local file or directory in the toolkit's own catalog format.
+    Local,
+}
+
+/// The AWS IAM Actions API source, cached at `~/.cache/aws_iam_expansion/aws_iam_actions.json`.
+///
+/// When the cache does not exist, the embedded catalog snapshot (if any) is used offline,
+/// or as a fallback when the API cannot be reached.
+pub struct AwsIamActionsSource {
+    /// The URL serving the AWS IAM actions JSON.
+    pub url: String,
+
+    /// Whether to avoid network requests, relying on the cache or the embedded snapshot.
+    pub offline: bool,
+}
+
+impl Default for AwsIamActionsSource {
+    fn default() -> Self {
+        Self {
+            url: crate::utils::AWS_IAM_ACTIONS_URL.to_string(),
+            offline: false,
+        }
+    }
+}
+
+impl CatalogSource for AwsIamActionsSource {
+    fn name(&self) -> String {
+        format!("AWS IAM Actions API ({})", self.url)
+    }
+
+    fn load(&self) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>> {
+        let iam_actions_json =
+            crate::utils::retrieve_iam_actions_json_from(&self.url, self.offline)?;
+        Ok(serde_json::from_str::<Vec<crate::types::AwsService>>(
+            &iam_actions_json,
+        )?)
+    }
+
+    fn update_cache(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.offline {
+            return Err("Cannot update the AWS IAM actions cache offline".into());
+        }
+        crate::utils::update_iam_actions_cache(&self.url)
+    }
+}
+
+/// A local copy of the AWS Service Authorization Reference, as a file or directory.
+pub struct ServiceReferenceSource {
+    /// The path to the JSON file or directory.
+    pub path: String,
+}
+
+impl CatalogSource for ServiceReferenceSource {
+    fn name(&self) -> String {
+        format!("Service Authorization Reference ({})", self.path)
+    }
+
+    fn load(&self) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>> {
+        eprintln!("[*] Loading AWS IAM actions from {}...", self.name());
+        crate::service_reference::load_service_reference(&self.path)
+    }
+}
+
+/// A local copy of the policy_sentry IAM definition database.
+pub struct PolicySentrySource {
+    /// The path to the `iam-definition.json` file.
+    pub path: String,
+}
+
+impl CatalogSource for PolicySentrySource {
+    fn name(&self) -> String {
+        format!("policy_sentry IAM definition ({})", self.path)
+    }
+
+    fn load(&self) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>> {
+        eprintln!("[*] Loading AWS IAM actions from {}...", self.name());
+        crate::policy_sentry::load_iam_definition(&self.path)
+    }
+}
+
+/// A local file or directory in the toolkit's own catalog format.
+///
+/// Each file holds a single service or an array of services, in the shape of the AWS IAM
+/// Actions API JSON, optionally with the metadata fields of [`AwsAction`](crate::types::AwsAction).
+pub struct LocalSource {
+    /// The path to the JSON file or directory.
+    pub path: String,
+}
+
+impl CatalogSource for LocalSource {
+    fn name(&self) -> String {
+        format!("local catalog ({})", self.path)
+    }
+
+    fn load(&self) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>> {
+        eprintln!("[*] Loading AWS IAM actions from {}...", self.name());
+        let mut services = Vec::new();
+        for file in json_files(&self.path)? {
+            let catalog_json = std::fs::read_to_string(&file)?;
+            services.extend(parse_catalog_json(&catalog_json).map_err(|error| {
+                format!(
+                    "Could not parse catalog file '{}': {}",
+                    file.display(),
+                    error
+                )
+            })?);
+        }
+        Ok(services)
+    }
+}
+
+/// Lists the JSON files of a path.
+///
+/// A file path is returned as is; a directory yields every `.json` file it contains, sorted.
+///
+/// # Arguments
+///
+/// * `path` - The path to a file or directory. A leading `~` is expanded.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn json_files(path: &str) -> Result<Vec<std::path::PathBuf>, Box<dyn std::error::Error>> {
+    let path = std::path::PathBuf::from(shellexpand::tilde(path).to_string());
+    if !path.is_dir() {
+        return Ok(vec![path]);
+    }
+
+    let mut files = std::fs::read_dir(&path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<std::path::PathBuf>, std::io::Error>>()?
+        .into_iter()
+        .filter(|file| {
+            file.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect::<Vec<std::path::PathBuf>>();
+    files.sort();
+    Ok(files)
+}
+
+/// Parses catalog JSON in the toolkit's own format.
+///
+/// The JSON may hold a single service or an array of services.
+///
+/// # Arguments
+///
+/// * `catalog_json` - The catalog JSON.
+///
+/// # Returns
+///
+/// A `Result` containing the services, or an error if the JSON is invalid.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::source::parse_catalog_json;
+///
+/// let services = parse_catalog_json(r#"{
+///     "service": "Amazon S3",
+///     "servicePrefix": "s3",
+///     "actions": [{ "action": "s3:GetObject", "type": "Read" }]
+/// }"#)
+/// .unwrap();
+/// assert_eq!(services[0].prefix, "s3");
+/// assert_eq!(services[0].actions[0].name, "s3:GetObject");
+/// ```
+pub fn parse_catalog_json(
+    catalog_json: &str,
+) -> Result<Vec<crate::types::AwsService>, Box<dyn std::error::Error>> {
+    match serde_json::from_str::<serde_json::Value>(catalog_json)? {
+        serde_json::Value::Array(services) => services
+            .into_iter()
+            .map(serde_json::from_value::<crate::types::AwsService>)
+            .collect::<Result<Vec<crate::types::AwsService>, serde_json::Error>>()
+            .map_err(|error| error.into()),
+        service => Ok(vec![serde_json::from_value::<crate::types::AwsService>(
+            service,
+        )?]),
+    }
+}
+
+/// The catalog source settings of the configuration file.
+///
+/// Every setting is optional; command-line flags take precedence over the file.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct CatalogConfig {
+    /// The kind of catalog source to load.
+    #[serde(default)]
+    pub catalog_source: Option<CatalogSourceKind>,
+
+    /// The path of the catalog, for local sources.
+    #[serde(default)]
+    pub catalog_path: Option<String>,
+
+    /// The URL to fetch the AWS IAM Actions API JSON from, instead of awsiamactions.io.
+    #[serde(default)]
+    pub catalog_url: Option<String>,
+
+    /// Whether to avoid network requests, relying on the cache or the embedded snapshot.
+    #[serde(default)]
+    pub offline: bool,
+}
+
+impl CatalogConfig {
+    /// Loads the configuration file at `~/.config/aws_iam_expansion/config.json`.
+    ///
+    /// A missing configuration file yields the default, empty configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let config_path = shellexpand::tilde("~/.config/aws_iam_expansion/config.json").to_string();
+        if !std::path::Path::new(&config_path).exists() {
+            return Ok(Self::default());
+        }
+
+        let config_json = std::fs::read_to_string(&config_path)?;
+        serde_json::from_str::<Self>(&config_json).map_err(|error| {
+            format!(
+                "Could not parse configuration file '{}': {}",
+                config_path, error
+            )
+            .into()
+        })
+    }
+}
+
+/// Selects the catalog source from the command-line flags and the configuration file.
+///
+/// The kind given on the command line wins over the configured one, and defaults to the
+/// AWS IAM Actions API. The configured path and URL are only used when the configured kind
+/// is the selected one, or when no kind is configured.
+///
+/// # Arguments
+///
+/// * `kind` - The kind of source given on the command line, if any.
+/// * `path` - The catalog path given on the command line, if any.
+/// * `config` - The configuration file settings.
+///
+/// # Returns
+///
+/// A `Result` containing the selected source, or an error if a local source has no path.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::source::{select_catalog_source, CatalogConfig, CatalogSourceKind};
+///
+/// let config = CatalogConfig {
+///     catalog_source: Some(CatalogSourceKind::PolicySentry),
+///     catalog_path: Some("iam-definition.json".to_string()),
+///     catalog_url: None,
+///     offline: false,
+/// };
+///
+/// let source = select_catalog_source(None, None, &config).unwrap();
+/// assert_eq!(source.name(), "policy_sentry IAM definition (iam-definition.json)");
+///
+/// let source = select_catalog_source(Some(CatalogSourceKind::Local), Some("catalog/"), &config)
+///     .unwrap();
+/// assert_eq!(source.name(), "local catalog (catalog/)");
+///
+/// assert!(select_catalog_source(Some(CatalogSourceKind::Local), None, &config).is_err());
+/// ```
+pub fn select_catalog_source(
+    kind: Option<CatalogSourceKind>,
+    path: Option<&str>,
+    config: &CatalogConfig,
+) -> Result<Box<dyn CatalogSource>, Box<dyn std::error::Error>> {
+    let selected = kind
+        .or(config.catalog_source)
+        .unwrap_or(CatalogSourceKind::AwsIamActions);
+    let configured = config.catalog_source.is_none_or(|kind| kind == selected);
+    let path = path
+        .map(str::to_string)
+        .or_else(|| config.catalog_path.clone().filter(|_| configured));
+
+    let require_path = |path: Option<String>| {
+        path.ok_or_else(|| {
+            format!(
+                "The '{}' catalog source requires a path (--catalog-path or 'catalog_path' in the configuration file)",
+                clap::ValueEnum::to_possible_value(&selected)
+                    .map(|value| value.get_name().to_string())
+                    .unwrap_or_default()
+            )
+        })
+    };
+
+    Ok(match selected {
+        CatalogSourceKind::AwsIamActions => Box::new(AwsIamActionsSource {
+            url: config
+                .catalog_url
+                .clone()
+                .filter(|_| configured)
+                .unwrap_or_else(|| crate::utils::AWS_IAM_ACTIONS_URL.to_string()),
+            offline: config.offline,
+        }),
+        CatalogSourceKind::ServiceReference => Box::new(ServiceReferenceSource {
+            path: require_path(path)?,
+        }),
+        CatalogSourceKind::PolicySentry => Box::new(PolicySentrySource {
+            path: require_path(path)?,
+        }),
+        CatalogSourceKind::Local => Box::new(LocalSource {
+            path: require_path(path)?,
+        }),
+    })
+}
@@ -0,0 +1,324 @@
+//! Extraction of the policy documents of Terraform plans.
+//!
+//! `terraform show -json` renders a plan as JSON, listing the planned attribute values of
+//! every resource in `planned_values` and the change planned for each resource in
+//! `resource_changes`. The policies of IAM and resource policy resources
+//! (`aws_iam_policy`, `aws_iam_role_policy`, `aws_s3_bucket_policy`, ...) are JSON strings
+//! within these values; this module decodes them so that they can be expanded and analyzed
+//! like any other policy, e.g., to gate `terraform apply` in CI on what they grant.
+
+use serde_json::Value;
+
+/// The attributes of Terraform resource types holding a policy document as a JSON string.
+const POLICY_ATTRIBUTES: &[(&str, &str)] = &[
+    ("aws_iam_policy", "policy"),
+    ("aws_iam_role_policy", "policy"),
+    ("aws_iam_user_policy", "policy"),
+    ("aws_iam_group_policy", "policy"),
+    ("aws_iam_role", "assume_role_policy"),
+    ("aws_s3_bucket_policy", "policy"),
+    ("aws_s3_access_point", "policy"),
+    ("aws_sqs_queue", "policy"),
+    ("aws_sqs_queue_policy", "policy"),
+    ("aws_sns_topic", "policy"),
+    ("aws_sns_topic_policy", "policy"),
+    ("aws_kms_key", "policy"),
+    ("aws_ecr_repository_policy", "policy"),
+    ("aws_secretsmanager_secret", "policy"),
+    ("aws_secretsmanager_secret_policy", "policy"),
+    ("aws_efs_file_system_policy", "policy"),
+    ("aws_backup_vault_policy", "policy"),
+    ("aws_glacier_vault", "access_policy"),
+    ("aws_opensearch_domain_policy", "access_policies"),
+    ("aws_elasticsearch_domain_policy", "access_policies"),
+];
+
+/// A policy document of a resource of a Terraform plan.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PlanPolicy {
+    /// The address of the resource holding the policy (e.g., "module.app.aws_iam_policy.read").
+    pub address: String,
+
+    /// The type of the resource holding the policy (e.g., "aws_iam_policy").
+    pub resource_type: String,
+
+    /// The attribute holding the policy (e.g., "policy", "inline_policy[0].policy").
+    pub attribute: String,
+
+    /// The name of the inline policy, for the `inline_policy` blocks of roles.
+    pub policy_name: Option<String>,
+
+    /// The actions Terraform plans for the resource (e.g., `["create"]`), if listed in
+    /// `resource_changes`.
+    pub change: Vec<String>,
+
+    /// The decoded policy document.
+    pub document: crate::types::AWSPolicyDocument,
+}
+
+impl std::fmt::Display for PlanPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.address, self.attribute)?;
+        if let Some(policy_name) = &self.policy_name {
+            write!(f, " ({})", policy_name)?;
+        }
+        Ok(())
+    }
+}
+
+/// The policy documents found in a Terraform plan.
+#[derive(Debug, Clone, Default)]
+pub struct PlanScan {
+    /// The decoded policies, in plan order.
+    pub policies: Vec<PlanPolicy>,
+
+    /// The policy attributes only known after apply (e.g., "aws_iam_policy.read policy"),
+    /// which cannot be analyzed.
+    pub unknown: Vec<String>,
+}
+
+/// A resource of a Terraform plan, with its planned values.
+struct PlanResource<'a> {
+    /// The address of the resource.
+    address: &'a str,
+
+    /// The type of the resource.
+    resource_type: &'a str,
+
+    /// The planned attribute values of the resource.
+    values: &'a Value,
+
+    /// The attributes of the resource only known after apply, as rendered in
+    /// `after_unknown`.
+    after_unknown: Option<&'a Value>,
+
+    /// The actions Terraform plans for the resource.
+    change: Vec<String>,
+}
+
+/// Collects the resources of a module of `planned_values` and of its child modules.
+fn module_resources<'a>(module: &'a Value, resources: &mut Vec<(&'a str, &'a str, &'a Value)>) {
+    for resource in module
+        .get("resources")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        if let (Some(address), Some(resource_type), Some(values)) = (
+            resource.get("address").and_then(Value::as_str),
+            resource.get("type").and_then(Value::as_str),
+            resource.get("values"),
+        ) {
+            resources.push((address, resource_type, values));
+        }
+    }
+    for child_module in module
+        .get("child_modules")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        module_resources(child_module, resources);
+    }
+}
+
+impl PlanScan {
+    /// Decodes the policy attribute of a resource, or records it as unknown.
+    fn add_policy(
+        &mut self,
+        resource: &PlanResource,
+        attribute: String,
+        pointer: &str,
+        policy_name: Option<String>,
+        value: Option<&Value>,
+    ) -> Result<(), String> {
+        match value {
+            Some(Value::String(policy_json)) if !policy_json.trim().is_empty() => {
+                let document = crate::policy::parse_policy(policy_json).map_err(|error| {
+                    format!(
+                        "Could not parse the policy of {} {}: {}",
+                        resource.address, attribute, error
+                    )
+                })?;
+                self.policies.push(PlanPolicy {
+                    address: resource.address.to_string(),
+                    resource_type: resource.resource_type.to_string(),
+                    attribute,
+                    policy_name,
+                    change: resource.change.clone(),
+                    document,
+                });
+            }
+            _ if resource
+                .after_unknown
+                .and_then(|after_unknown| after_unknown.pointer(pointer))
+                == Some(&Value::Bool(true)) =>
+            {
+                self.unknown
+                    .push(format!("{} {}", resource.address, attribute));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Decodes the policy attributes of a resource.
+    fn add_resource(&mut self, resource: &PlanResource) -> Result<(), String> {
+        for (resource_type, attribute) in POLICY_ATTRIBUTES {
+            if *resource_type == resource.resource_type {
+                self.add_policy(
+                    resource,
+                    attribute.to_string(),
+                    &format!("/{}", attribute),
+                    None,
+                    resource.values.get(*attribute),
+                )?;
+            }
+        }
+
+        if resource.resource_type == "aws_iam_role" {
+            for (index, inline_policy) in resource
+                .values
+                .get("inline_policy")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                self.add_policy(
+                    resource,
+                    format!("inline_policy[{}].policy", index),
+                    &format!("/inline_policy/{}/policy", index),
+                    inline_policy
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string),
+                    inline_policy.get("policy"),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the policy documents of the resources of a Terraform plan.
+///
+/// The resources of `planned_values`, including those of child modules, are walked first,
+/// followed by the resources of `resource_changes` missing from `planned_values`. Resources
+/// planned for deletion are skipped. The JSON-string policy attributes of the IAM resource
+/// types (`aws_iam_policy`, `aws_iam_role_policy`, `aws_iam_user_policy`,
+/// `aws_iam_group_policy`, and the `assume_role_policy` and `inline_policy` blocks of
+/// `aws_iam_role`) and of resource policy types (e.g., `aws_s3_bucket_policy`,
+/// `aws_sqs_queue_policy`, `aws_kms_key`) are decoded.
+///
+/// # Arguments
+///
+/// * `plan` - The plan, as rendered by `terraform show -json`.
+///
+/// # Returns
+///
+/// A `Result` containing the decoded policies, and the policies only known after apply.
+///
+/// # Errors
+///
+/// Returns an error naming the resource address and attribute of the first policy that
+/// cannot be parsed.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::terraform::plan_policies;
+///
+/// let plan = serde_json::json!({
+///     "planned_values": { "root_module": {
+///         "resources": [],
+///         "child_modules": [{ "resources": [{
+///             "address": "module.app.aws_iam_policy.read",
+///             "type": "aws_iam_policy",
+///             "values": { "policy": "{\"Statement\":[{\"Effect\":\"Allow\",\"Action\":\"s3:Get*\",\"Resource\":\"*\"}]}" }
+///         }] }]
+///     } },
+///     "resource_changes": [
+///         { "address": "module.app.aws_iam_policy.read", "type": "aws_iam_policy",
+///           "change": { "actions": ["create"], "after": {}, "after_unknown": {} } },
+///         { "address": "aws_s3_bucket_policy.logs", "type": "aws_s3_bucket_policy",
+///           "change": { "actions": ["create"], "after": {}, "after_unknown": { "policy": true } } }
+///     ]
+/// });
+///
+/// let scan = plan_policies(&plan).unwrap();
+/// assert_eq!(scan.policies[0].to_string(), "module.app.aws_iam_policy.read policy");
+/// assert_eq!(scan.policies[0].change, vec!["create"]);
+/// assert_eq!(scan.unknown, vec!["aws_s3_bucket_policy.logs policy"]);
+/// ```
+pub fn plan_policies(plan: &Value) -> Result<PlanScan, Box<dyn std::error::Error>> {
+    let changes = plan
+        .get("resource_changes")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let change_of = |address: &str| {
+        changes
+            .iter()
+            .find(|change| change.get("address").and_then(Value::as_str) == Some(address))
+            .and_then(|change| change.get("change"))
+    };
+    let actions_of = |change: Option<&Value>| {
+        change
+            .and_then(|change| change.get("actions"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect::<Vec<String>>()
+    };
+
+    let mut planned: Vec<(&str, &str, &Value)> = Vec::new();
+    if let Some(root_module) = plan
+        .get("planned_values")
+        .and_then(|planned_values| planned_values.get("root_module"))
+    {
+        module_resources(root_module, &mut planned);
+    }
+
+    let mut scan = PlanScan::default();
+    for (address, resource_type, values) in &planned {
+        let change = change_of(address);
+        scan.add_resource(&PlanResource {
+            address,
+            resource_type,
+            values,
+            after_unknown: change.and_then(|change| change.get("after_unknown")),
+            change: actions_of(change),
+        })?;
+    }
+
+    for resource_change in changes {
+        let (Some(address), Some(resource_type), Some(change)) = (
+            resource_change.get("address").and_then(Value::as_str),
+            resource_change.get("type").and_then(Value::as_str),
+            resource_change.get("change"),
+        ) else {
+            continue;
+        };
+        let actions = actions_of(Some(change));
+        if planned.iter().any(|(planned, _, _)| *planned == address)
+            || actions.iter().all(|action| action == "delete")
+        {
+            continue;
+        }
+        let Some(values) = change.get("after").filter(|after| after.is_object()) else {
+            continue;
+        };
+        scan.add_resource(&PlanResource {
+            address,
+            resource_type,
+            values,
+            after_unknown: change.get("after_unknown"),
+            change: actions,
+        })?;
+    }
+    Ok(scan)
+}
@@ -0,0 +1,18 @@
+//! Catalogs shared by the unit tests.
+
+/// Parses services from their JSON representation, in the AWS IAM actions format.
+pub(crate) fn services(services: serde_json::Value) -> Vec<crate::types::AwsService> {
+    serde_json::from_value::<Vec<crate::types::AwsService>>(services).unwrap()
+}
+
+/// Builds a catalog from the JSON representation of its services.
+pub(crate) fn catalog(services: serde_json::Value) -> crate::catalog::Catalog {
+    crate::catalog::Catalog::new(self::services(services))
+}
+
+/// Builds the catalog of `catalog/fixture_aws_iam_actions.json`.
+///
+/// It holds a few `iam`, `s3` and `sts` actions covering every access level.
+pub(crate) fn fixture_catalog() -> crate::catalog::Catalog {
+    catalog(serde_json::from_str(include_str!("../catalog/fixture_aws_iam_actions.json")).unwrap())
+}
@@ -8,7 +8,7 @@
 /// An action is a specific permission that can be granted or denied in an AWS IAM policy.
 /// Actions are associated with a particular service and have a type indicating their
 /// category (e.g., "Read", "Write", "List", etc.).
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AwsAction {
     /// The name of the action (e.g., "s3:GetObject", "ec2:DescribeInstances").
     ///
@@ -21,6 +21,60 @@ pub struct AwsAction {
     /// Deserialized from the "type" field in the JSON source.
     #[serde(rename = "type")]
     pub action_type: String,
+
+    /// The resource types the action can be scoped to, with their ARN formats.
+    ///
+    /// Only available when the catalog is loaded from a source providing it, such as the
+    /// AWS Service Authorization Reference. Empty otherwise.
+    #[serde(
+        rename = "resource_types",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub resource_types: Vec<AwsResourceType>,
+
+    /// The condition keys supported by the action (e.g., "aws:RequestTag/${TagKey}").
+    ///
+    /// Only available when the catalog is loaded from a source providing it. Empty otherwise.
+    #[serde(
+        rename = "condition_keys",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub condition_keys: Vec<String>,
+
+    /// The additional actions a principal needs to be granted to perform the action.
+    ///
+    /// Only available when the catalog is loaded from a source providing it. Empty otherwise.
+    #[serde(
+        rename = "dependent_actions",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub dependent_actions: Vec<String>,
+}
+
+/// Represents a resource type an AWS IAM action can be scoped to.
+///
+/// Resource types describe which ARNs can appear in the `Resource` element of a statement
+/// granting the action, and which condition keys apply to them.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AwsResourceType {
+    /// The name of the resource type (e.g., "bucket", "object", "role").
+    pub name: String,
+
+    /// The ARN formats of the resource type
+    /// (e.g., "arn:${Partition}:s3:::${BucketName}/${ObjectName}").
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arn_formats: Vec<String>,
+
+    /// The condition keys that apply to the resource type.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub condition_keys: Vec<String>,
+
+    /// Whether the resource type is required for the action, if the source specifies it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
 }
 
 /// Represents an AWS service and its available actions.
@@ -28,7 +82,7 @@ pub struct AwsAction {
 /// A service is a high-level AWS offering (e.g., S3, EC2, IAM) that contains multiple
 /// related IAM actions. This structure maps a service to all the actions that can be
 /// performed on it.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AwsService {
     /// The full name of the AWS service (e.g., "Amazon Simple Storage Service").
     ///
@@ -52,45 +106,378 @@ pub struct AwsService {
     pub actions: Vec<AwsAction>,
 }
 
+/// A policy element holding either a single value or an array of values.
+///
+/// IAM accepts both `"Action": "s3:GetObject"` and `"Action": ["s3:GetObject"]`, and
+/// likewise a single statement object or an array of statements; the form used in the
+/// policy is kept when it is written back.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::types::OneOrMany;
+///
+/// let one = serde_json::from_str::<OneOrMany<String>>(r#""s3:*""#).unwrap();
+/// let many = serde_json::from_str::<OneOrMany<String>>(r#"["s3:Get*", "iam:PassRole"]"#).unwrap();
+/// assert_eq!(one.iter().collect::<Vec<&String>>(), vec!["s3:*"]);
+/// assert_eq!(many.len(), 2);
+/// assert_eq!(serde_json::to_string(&one).unwrap(), r#""s3:*""#);
+/// ```
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    /// A single value.
+    One(T),
+
+    /// An array of values.
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    /// Returns an iterator over the values.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        match self {
+            OneOrMany::One(value) => std::slice::from_ref(value).iter(),
+            OneOrMany::Many(values) => values.iter(),
+        }
+    }
+
+    /// Returns an iterator over the values, allowing them to be modified.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        match self {
+            OneOrMany::One(value) => std::slice::from_mut(value).iter_mut(),
+            OneOrMany::Many(values) => values.iter_mut(),
+        }
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.iter().len()
+    }
+
+    /// Returns whether there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keeps the values for which the predicate returns `true`, in order.
+    ///
+    /// A single value that is dropped leaves an empty array.
+    pub fn retain(&mut self, mut predicate: impl FnMut(&T) -> bool) {
+        match self {
+            OneOrMany::One(value) => {
+                if !predicate(value) {
+                    *self = OneOrMany::Many(Vec::new());
+                }
+            }
+            OneOrMany::Many(values) => values.retain(|value| predicate(value)),
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for OneOrMany<T> {
+    fn from(values: Vec<T>) -> Self {
+        OneOrMany::Many(values)
+    }
+}
+
+/// The `Principal` or `NotPrincipal` element of a policy statement.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::types::Principal;
+///
+/// let principal = serde_json::from_str::<Principal>(
+///     r#"{ "AWS": "arn:aws:iam::123456789012:root", "Service": ["ec2.amazonaws.com"] }"#,
+/// )
+/// .unwrap();
+/// let Principal::Specific(principals) = &principal else { unreachable!() };
+/// assert_eq!(principals["Service"].len(), 1);
+/// assert_eq!(serde_json::from_str::<Principal>(r#""*""#).unwrap(), Principal::Wildcard("*".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Principal {
+    /// Every principal (`"Principal": "*"`).
+    Wildcard(String),
+
+    /// The principals of each kind ("AWS", "Service", "Federated", "CanonicalUser"), in
+    /// policy order.
+    Specific(indexmap::IndexMap<String, OneOrMany<String>>),
+}
+
+/// A value of a condition key in the `Condition` element of a policy statement.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ConditionValue {
+    /// A string value (e.g., an ARN, a tag value or a date).
+    String(String),
+
+    /// A boolean value (e.g., for `Bool` conditions).
+    Bool(bool),
+
+    /// A numeric value (e.g., for `NumericLessThan` conditions).
+    Number(serde_json::Number),
+}
+
+/// The `Condition` element of a policy statement, mapping condition operators
+/// (e.g., "StringEquals") to condition keys and their values, in policy order.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::types::{Condition, ConditionValue};
+///
+/// let condition = serde_json::from_str::<Condition>(
+///     r#"{ "Bool": { "aws:SecureTransport": false }, "StringEquals": { "aws:RequestedRegion": ["eu-west-1"] } }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     condition["Bool"]["aws:SecureTransport"].iter().next(),
+///     Some(&ConditionValue::Bool(false))
+/// );
+/// assert_eq!(condition.keys().collect::<Vec<&String>>(), vec!["Bool", "StringEquals"]);
+/// ```
+pub type Condition =
+    indexmap::IndexMap<String, indexmap::IndexMap<String, OneOrMany<ConditionValue>>>;
+
+/// The elements of a policy statement, as spelled by the IAM policy grammar.
+pub const STATEMENT_ELEMENTS: &[&str] = &[
+    "Sid",
+    "Effect",
+    "Principal",
+    "NotPrincipal",
+    "Action",
+    "NotAction",
+    "Resource",
+    "NotResource",
+    "Condition",
+];
+
+/// The top-level elements of a policy document, as spelled by the IAM policy grammar.
+pub const DOCUMENT_ELEMENTS: &[&str] = &["Version", "Id", "Statement"];
+
+/// Returns the element of the IAM policy grammar a key refers to, ignoring case.
+///
+/// # Arguments
+///
+/// * `key` - The key as written in the policy (e.g., "effect").
+/// * `elements` - The known elements, such as [`STATEMENT_ELEMENTS`].
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::types::{canonical_element, STATEMENT_ELEMENTS};
+///
+/// assert_eq!(canonical_element("notaction", STATEMENT_ELEMENTS), Some("NotAction"));
+/// assert_eq!(canonical_element("Comment", STATEMENT_ELEMENTS), None);
+/// ```
+pub fn canonical_element(key: &str, elements: &[&'static str]) -> Option<&'static str> {
+    elements
+        .iter()
+        .find(|element| element.eq_ignore_ascii_case(key))
+        .copied()
+}
+
+/// Renames the keys of a JSON object referring to a known element, ignoring case, to the
+/// spelling of the element, so that `"effect"` deserializes like `"Effect"`.
+///
+/// # Errors
+///
+/// Returns an error if an element is given more than once (e.g., both `"Effect"` and
+/// `"effect"`).
+fn canonicalize_keys<E: serde::de::Error>(
+    object: serde_json::Map<String, serde_json::Value>,
+    elements: &[&'static str],
+) -> Result<serde_json::Map<String, serde_json::Value>, E> {
+    let mut canonical = serde_json::Map::new();
+    for (key, value) in object {
+        let key = canonical_element(&key, elements)
+            .map(|element| element.to_string())
+            .unwrap_or(key);
+        if canonical.contains_key(&key) {
+            return Err(E::custom(format!("duplicate {} element", key)));
+        }
+        canonical.insert(key, value);
+    }
+    Ok(canonical)
+}
+
+/// Reorders the keys of a JSON object to follow the key order of the original document.
+///
+/// Keys of the original order come first, in that order and spelling (e.g., `"effect"`
+/// for the `Effect` element); keys it does not list (e.g., an `Action` element replacing
+/// a `NotAction` element) follow in serialization order.
+fn with_key_order(value: serde_json::Value, key_order: &[String]) -> serde_json::Value {
+    let serde_json::Value::Object(mut object) = value else {
+        return value;
+    };
+
+    let mut ordered = serde_json::Map::new();
+    for key in key_order {
+        let value = object.shift_remove(key).or_else(|| {
+            let element = object
+                .keys()
+                .find(|element| element.eq_ignore_ascii_case(key))
+                .cloned()?;
+            object.shift_remove(&element)
+        });
+        if let Some(value) = value {
+            ordered.insert(key.clone(), value);
+        }
+    }
+    ordered.extend(object);
+    serde_json::Value::Object(ordered)
+}
+
 /// Represents a single statement within an AWS IAM policy document.
 /// A policy statement defines the permissions granted or denied for specific actions
 /// on AWS resources.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+///
+/// Element names are matched ignoring case (e.g., `"effect"` for `Effect`). Statements
+/// round-trip losslessly: the key order and spelling of the original statement and any
+/// element the model does not know are kept when the statement is written back.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(remote = "Self")]
 pub struct AWSPolicyStatement {
-    #[serde(rename = "Sid", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "Sid", default, skip_serializing_if = "Option::is_none")]
     pub sid: Option<String>,
 
     #[serde(rename = "Effect")]
     pub effect: String,
 
-    #[serde(rename = "Principal", skip_serializing_if = "Option::is_none")]
-    pub principal: Option<serde_json::Value>,
+    #[serde(rename = "Principal", default, skip_serializing_if = "Option::is_none")]
+    pub principal: Option<Principal>,
+
+    #[serde(
+        rename = "NotPrincipal",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub not_principal: Option<Principal>,
+
+    #[serde(rename = "Action", default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<OneOrMany<String>>,
 
-    #[serde(rename = "Action")]
-    pub action: serde_json::Value,
+    #[serde(rename = "NotAction", default, skip_serializing_if = "Option::is_none")]
+    pub not_action: Option<OneOrMany<String>>,
 
-    #[serde(rename = "NotAction", skip_serializing_if = "Option::is_none")]
-    pub not_action: Option<serde_json::Value>,
+    #[serde(rename = "Resource", default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<OneOrMany<String>>,
 
-    #[serde(rename = "Resource", skip_serializing_if = "Option::is_none")]
-    pub resource: Option<serde_json::Value>,
+    #[serde(
+        rename = "NotResource",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub not_resource: Option<OneOrMany<String>>,
 
-    #[serde(rename = "NotResource", skip_serializing_if = "Option::is_none")]
-    pub not_resource: Option<serde_json::Value>,
+    #[serde(rename = "Condition", default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Condition>,
 
-    #[serde(rename = "Condition", skip_serializing_if = "Option::is_none")]
-    pub condition: Option<serde_json::Value>,
+    /// The elements of the statement the model does not know, in policy order.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+
+    /// The keys of the statement, in policy order.
+    #[serde(skip)]
+    pub key_order: Vec<String>,
 }
 
-/// Represents a single statement within an AWS IAM policy document.
-/// A policy statement defines the permissions granted or denied for specific actions
-/// on AWS resources.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+impl<'de> serde::Deserialize<'de> for AWSPolicyStatement {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let object = serde_json::Map::<String, serde_json::Value>::deserialize(deserializer)?;
+        let key_order = object.keys().cloned().collect::<Vec<String>>();
+        let object = canonicalize_keys(object, STATEMENT_ELEMENTS)?;
+        let mut statement = AWSPolicyStatement::deserialize(serde_json::Value::Object(object))
+            .map_err(serde::de::Error::custom)?;
+        statement.key_order = key_order;
+        Ok(statement)
+    }
+}
+
+impl serde::Serialize for AWSPolicyStatement {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let value = AWSPolicyStatement::serialize(self, serde_json::value::Serializer)
+            .map_err(serde::ser::Error::custom)?;
+        with_key_order(value, &self.key_order).serialize(serializer)
+    }
+}
+
+/// Represents an AWS IAM policy document, made of one or more statements.
+///
+/// The document accepts the grammar IAM accepts: `Version` may be omitted, `Statement`
+/// may be a single statement object, and element names are matched ignoring case.
+/// Documents round-trip losslessly: the key order and spelling of the original document,
+/// the form of its `Statement` element and any element the model does not know are kept
+/// when the document is written back.
+///
+/// # Examples
+///
+/// ```
+/// use aws_iam_expansion::types::AWSPolicyDocument;
+///
+/// let policy_json = r#"{"Statement":[{"Effect":"Allow","Action":"s3:GetObject","Resource":"*","Sid":"Read"}],"Version":"2012-10-17","Comment":"kept"}"#;
+/// let policy = serde_json::from_str::<AWSPolicyDocument>(policy_json).unwrap();
+/// assert_eq!(policy.statement.iter().next().unwrap().sid.as_deref(), Some("Read"));
+/// assert_eq!(serde_json::to_string(&policy).unwrap(), policy_json);
+///
+/// let policy_json = r#"{"statement":{"effect":"Deny","action":"iam:*","resource":"*"}}"#;
+/// let policy = serde_json::from_str::<AWSPolicyDocument>(policy_json).unwrap();
+/// assert_eq!(policy.version, None);
+/// assert_eq!(policy.statement.len(), 1);
+/// assert_eq!(serde_json::to_string(&policy).unwrap(), policy_json);
+/// ```
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(remote = "Self")]
 pub struct AWSPolicyDocument {
-    #[serde(rename = "Version")]
-    pub version: String,
-    #[serde(rename = "Id", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "Version", default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(rename = "Id", default, skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
+
     #[serde(rename = "Statement")]
-    pub statement: Vec<AWSPolicyStatement>,
+    pub statement: OneOrMany<AWSPolicyStatement>,
+
+    /// The elements of the document the model does not know, in policy order.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+
+    /// The keys of the document, in policy order.
+    #[serde(skip)]
+    pub key_order: Vec<String>,
+}
+
+impl<'de> serde::Deserialize<'de> for AWSPolicyDocument {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let object = serde_json::Map::<String, serde_json::Value>::deserialize(deserializer)?;
+        let key_order = object.keys().cloned().collect::<Vec<String>>();
+        let object = canonicalize_keys(object, DOCUMENT_ELEMENTS)?;
+        let mut document = AWSPolicyDocument::deserialize(serde_json::Value::Object(object))
+            .map_err(serde::de::Error::custom)?;
+        document.key_order = key_order;
+        Ok(document)
+    }
+}
+
+impl serde::Serialize for AWSPolicyDocument {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let value = AWSPolicyDocument::serialize(self, serde_json::value::Serializer)
+            .map_err(serde::ser::Error::custom)?;
+        with_key_order(value, &self.key_order).serialize(serializer)
+    }
 }
@@ -0,0 +1,34 @@
+// Shared by the library (`utils`) and the build script, which both `include!` this file
+// to format the date of catalog caches and snapshots.
+
+/// Formats a number of seconds since the Unix epoch as a `YYYY-MM-DD` date (UTC).
+///
+/// # Arguments
+///
+/// * `seconds` - The number of seconds since the Unix epoch.
+///
+/// # Examples
+///
+/// ```
+/// # use aws_iam_expansion::utils::format_unix_date;
+/// assert_eq!(format_unix_date(0), "1970-01-01");
+/// assert_eq!(format_unix_date(1_709_164_800), "2024-02-29");
+/// ```
+pub fn format_unix_date(seconds: u64) -> String {
+    // Civil-from-days conversion, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
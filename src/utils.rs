@@ -2,9 +2,13 @@
 //!
 //! This module provides functionality to fetch and cache AWS IAM actions data from
 //! the AWS IAM Actions JSON API. It manages local caching to reduce network requests
-//! and improve performance on subsequent runs.
+//! and improve performance on subsequent runs, and can fall back to a catalog snapshot
+//! compiled into the binary with the `embedded-catalog` feature. Every cache replaced by
+//! `update-cache` is archived, so that actions missing from later catalogs can be traced.
+//! It also provides helpers to read IAM policy documents from local files.
 
-const AWS_IAM_ACTIONS_URL: &str = "https://www.awsiamactions.io/json";
+/// The URL of the AWS IAM Actions JSON API, the default catalog source.
+pub const AWS_IAM_ACTIONS_URL: &str = "https://www.awsiamactions.io/json";
 
 /// Determines the file path for the AWS IAM actions cache.
 ///
@@ -30,6 +34,20 @@ fn cache_file_path() -> String {
         .to_string()
 }
 
+/// Determines the file path recording the URL the AWS IAM actions cache was fetched from.
+///
+/// Returns the path `~/.cache/aws_iam_expansion/aws_iam_actions.source`, next to the cache.
+fn cache_source_file_path() -> std::path::PathBuf {
+    std::path::PathBuf::from(cache_file_path()).with_extension("source")
+}
+
+/// Determines the directory holding the archived AWS IAM actions caches.
+///
+/// Returns the path `~/.cache/aws_iam_expansion/history`. The directory is not created.
+fn cache_history_dir_path() -> std::path::PathBuf {
+    std::path::PathBuf::from(shellexpand::tilde("~/.cache/aws_iam_expansion/history").to_string())
+}
+
 /// Retrieves the AWS IAM actions JSON data, using cache when available.
 ///
 /// This function first checks if a cached copy of AWS IAM actions exists locally.
@@ -47,30 +65,241 @@ fn cache_file_path() -> String {
 /// # Examples
 ///
 /// ```no_run
+/// # use aws_iam_expansion::{types::AwsService, utils::retrieve_iam_actions_json};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let iam_actions_json = retrieve_iam_actions_json()?;
 /// let services = serde_json::from_str::<Vec<AwsService>>(&iam_actions_json)?;
+/// # Ok(())
+/// # }
 /// ```
 pub fn retrieve_iam_actions_json() -> Result<String, Box<dyn std::error::Error>> {
+    retrieve_iam_actions_json_from(AWS_IAM_ACTIONS_URL, false)
+}
+
+/// Retrieves the AWS IAM actions JSON data from a given URL, using cache when available.
+///
+/// This behaves like [`retrieve_iam_actions_json`], but fetches the data from `url` instead
+/// of the default AWS IAM Actions API, which allows using a mirror serving the same JSON
+/// shape. The cache is only used when it was fetched from `url`, and is replaced with the
+/// data of `url` otherwise, except in offline mode, where it is used with a warning.
+///
+/// When the binary embeds a catalog snapshot (the `embedded-catalog` feature) and there is
+/// no cache, the snapshot is used instead of the network in offline mode, and as a fallback
+/// if the data cannot be fetched otherwise. The snapshot is never written to the cache.
+///
+/// # Arguments
+///
+/// * `url` - The URL serving the AWS IAM actions JSON.
+/// * `offline` - Whether to avoid network requests entirely.
+///
+/// # Returns
+///
+/// A `Result` containing:
+/// - `Ok(String)`: The JSON string containing all available AWS IAM actions and services
+/// - `Err(Box<dyn std::error::Error>)`: An error if the request fails or file operations fail,
+///   and no embedded snapshot is available
+///
+/// # Examples
+///
+/// ```no_run
+/// # use aws_iam_expansion::utils::retrieve_iam_actions_json_from;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let iam_actions_json =
+///     retrieve_iam_actions_json_from("https://mirror.example.com/iam.json", false)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn retrieve_iam_actions_json_from(
+    url: &str,
+    offline: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
     let cache_path = cache_file_path();
     if std::path::Path::new(&cache_path).exists() {
-        println!("[*] Using cached AWS IAM actions data...");
-        let cached_data = std::fs::read_to_string(&cache_path)?;
-        Ok(cached_data)
-    } else {
-        println!("[*] Fetching AWS IAM actions...");
-        let iam_actions_json = reqwest::blocking::Client::new()
-            .get(AWS_IAM_ACTIONS_URL)
-            .header(
-                "User-Agent",
-                "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:146.0) Gecko/20100101 Firefox/146.0",
-            )
-            .send()?
-            .text()?;
-        std::fs::write(&cache_path, &iam_actions_json)?;
-        Ok(iam_actions_json)
+        // Caches fetched before their URL was recorded come from the default URL.
+        let source =
+            read_iam_actions_cache_source().unwrap_or_else(|| AWS_IAM_ACTIONS_URL.to_string());
+        if source == url || offline {
+            if source != url {
+                eprintln!(
+                    "[!] Warning: the AWS IAM actions cache was fetched from {}, not {}, and cannot be refreshed offline.",
+                    source, url
+                );
+            }
+            eprintln!("[*] Using cached AWS IAM actions data...");
+            let cached_data = std::fs::read_to_string(&cache_path)?;
+            return Ok(cached_data);
+        }
+        eprintln!(
+            "[*] The AWS IAM actions cache was fetched from {}, replacing it with the data from {}...",
+            source, url
+        );
+    }
+
+    if offline {
+        let snapshot = embedded_snapshot().ok_or(
+            "No cached AWS IAM actions data and no embedded snapshot to use offline \
+             (build with `--features embedded-catalog` to embed one)",
+        )?;
+        eprintln!(
+            "[*] Using embedded AWS IAM actions snapshot from {}...",
+            snapshot.date
+        );
+        return Ok(snapshot.json.to_string());
+    }
+
+    match fetch_iam_actions_json(url) {
+        Ok(iam_actions_json) => Ok(iam_actions_json),
+        Err(error) => {
+            let snapshot = embedded_snapshot().ok_or(error.to_string())?;
+            eprintln!(
+                "[!] Could not fetch AWS IAM actions ({}), falling back to the embedded snapshot from {}...",
+                error, snapshot.date
+            );
+            Ok(snapshot.json.to_string())
+        }
+    }
+}
+
+/// Fetches the AWS IAM actions JSON data from a given URL and stores it in the cache, along
+/// with the URL it was fetched from.
+///
+/// # Arguments
+///
+/// * `url` - The URL serving the AWS IAM actions JSON.
+///
+/// # Returns
+///
+/// A `Result` containing the fetched JSON, or an error if the request or the cache write fails.
+fn fetch_iam_actions_json(url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    eprintln!("[*] Fetching AWS IAM actions...");
+    let iam_actions_json = reqwest::blocking::Client::new()
+        .get(url)
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:146.0) Gecko/20100101 Firefox/146.0",
+        )
+        .send()?
+        .error_for_status()?
+        .text()?;
+    std::fs::write(cache_file_path(), &iam_actions_json)?;
+    std::fs::write(cache_source_file_path(), url)?;
+    Ok(iam_actions_json)
+}
+
+/// A catalog snapshot compiled into the binary with the `embedded-catalog` feature.
+#[derive(Debug, Clone, Copy)]
+pub struct CatalogSnapshot {
+    /// The date of the snapshot (`YYYY-MM-DD`), or "unknown".
+    pub date: &'static str,
+
+    /// The AWS IAM actions JSON of the snapshot.
+    pub json: &'static str,
+}
+
+/// Returns the catalog snapshot compiled into the binary, if any.
+///
+/// A snapshot is only available when the toolkit is built with the `embedded-catalog` feature.
+///
+/// # Examples
+///
+/// ```
+/// # use aws_iam_expansion::utils::embedded_snapshot;
+/// match embedded_snapshot() {
+///     Some(snapshot) => println!("Embedded snapshot from {}", snapshot.date),
+///     None => println!("No embedded snapshot"),
+/// }
+/// ```
+pub fn embedded_snapshot() -> Option<CatalogSnapshot> {
+    #[cfg(feature = "embedded-catalog")]
+    {
+        Some(CatalogSnapshot {
+            date: env!("AWS_IAM_EXPANSION_SNAPSHOT_DATE"),
+            json: include_str!(concat!(env!("OUT_DIR"), "/aws_iam_actions.json")),
+        })
+    }
+    #[cfg(not(feature = "embedded-catalog"))]
+    {
+        None
+    }
+}
+
+/// Reads the AWS IAM actions cache, with its date.
+///
+/// The date is the last modification time of the cache file, formatted as `YYYY-MM-DD` (UTC).
+///
+/// # Returns
+///
+/// A `Result` containing the date and JSON of the cache, `None` if there is no cache, or an
+/// error if the cache cannot be read.
+pub fn read_iam_actions_cache() -> Result<Option<(String, String)>, Box<dyn std::error::Error>> {
+    let cache_path = cache_file_path();
+    if !std::path::Path::new(&cache_path).exists() {
+        return Ok(None);
+    }
+
+    let modified = std::fs::metadata(&cache_path)?
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)?;
+    let cached_data = std::fs::read_to_string(&cache_path)?;
+    Ok(Some((format_unix_date(modified.as_secs()), cached_data)))
+}
+
+/// Reads the URL the AWS IAM actions cache was fetched from, if recorded.
+///
+/// Caches fetched before the URL was recorded have no source.
+pub fn read_iam_actions_cache_source() -> Option<String> {
+    std::fs::read_to_string(cache_source_file_path())
+        .ok()
+        .map(|source| source.trim().to_string())
+}
+
+/// An AWS IAM actions cache archived by [`update_iam_actions_cache`].
+#[derive(Debug, Clone)]
+pub struct ArchivedCache {
+    /// The date of the cache (`YYYY-MM-DD`).
+    pub date: String,
+
+    /// The URL the cache was fetched from, if recorded.
+    pub source: Option<String>,
+
+    /// The AWS IAM actions JSON of the cache.
+    pub json: String,
+}
+
+/// Reads the AWS IAM actions caches archived by [`update_iam_actions_cache`].
+///
+/// # Returns
+///
+/// A `Result` containing every archived cache, oldest first, or an error if the history
+/// directory or an archived cache cannot be read.
+pub fn read_iam_actions_history() -> Result<Vec<ArchivedCache>, Box<dyn std::error::Error>> {
+    let history_dir = cache_history_dir_path();
+    if !history_dir.is_dir() {
+        return Ok(Vec::new());
     }
+
+    let mut history: Vec<ArchivedCache> = Vec::new();
+    for file in crate::source::json_files(&history_dir.to_string_lossy())? {
+        let Some(date) = file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.strip_prefix("aws_iam_actions-"))
+        else {
+            continue;
+        };
+        history.push(ArchivedCache {
+            date: date.to_string(),
+            source: std::fs::read_to_string(file.with_extension("source"))
+                .ok()
+                .map(|source| source.trim().to_string()),
+            json: std::fs::read_to_string(&file)?,
+        });
+    }
+    Ok(history)
 }
 
+include!("unix_date.rs");
+
 /// Deletes the cached AWS IAM actions data file.
 ///
 /// This function removes the cache file located at `~/.cache/aws_iam_expansion/aws_iam_actions.json`
@@ -83,23 +312,37 @@ pub fn retrieve_iam_actions_json() -> Result<String, Box<dyn std::error::Error>>
 /// # Examples
 ///
 /// ```no_run
+/// # use aws_iam_expansion::utils::delete_iam_actions_cache;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// delete_iam_actions_cache()?;
+/// # Ok(())
+/// # }
 /// ```
 pub fn delete_iam_actions_cache() -> Result<(), Box<dyn std::error::Error>> {
     let cache_path = cache_file_path();
     if std::path::Path::new(&cache_path).exists() {
         std::fs::remove_file(&cache_path)?;
-        println!("[*] Deleted AWS IAM actions cache.");
+        if cache_source_file_path().exists() {
+            std::fs::remove_file(cache_source_file_path())?;
+        }
+        eprintln!("[*] Deleted AWS IAM actions cache.");
     } else {
-        println!("[!] No AWS IAM actions cache found to delete.");
+        eprintln!("[!] No AWS IAM actions cache found to delete.");
     }
     Ok(())
 }
 
 /// Updates the cached AWS IAM actions data by fetching the latest version.
 ///
-/// This function deletes the existing cache file (if any) and retrieves the latest
-/// AWS IAM actions data from the API, storing it in the cache for future use.
+/// This function archives the existing cache file (if any) into
+/// `~/.cache/aws_iam_expansion/history`, named after its date and along with the URL it was
+/// fetched from, deletes it and fetches the latest AWS IAM actions data from the given URL,
+/// storing it in the cache for future use. The embedded catalog snapshot, if any, is never
+/// used as a fallback here.
+///
+/// # Arguments
+///
+/// * `url` - The URL serving the AWS IAM actions JSON, i.e. the configured catalog source.
 ///
 /// # Returns
 ///
@@ -108,11 +351,65 @@ pub fn delete_iam_actions_cache() -> Result<(), Box<dyn std::error::Error>> {
 /// # Examples
 ///
 /// ```no_run
-/// update_iam_actions_cache()?;
+/// # use aws_iam_expansion::utils::{update_iam_actions_cache, AWS_IAM_ACTIONS_URL};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// update_iam_actions_cache(AWS_IAM_ACTIONS_URL)?;
+/// # Ok(())
+/// # }
 /// ```
-pub fn update_iam_actions_cache() -> Result<(), Box<dyn std::error::Error>> {
+pub fn update_iam_actions_cache(url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some((date, cached_data)) = read_iam_actions_cache()? {
+        let history_dir = cache_history_dir_path();
+        std::fs::create_dir_all(&history_dir)?;
+        let archive_path = history_dir.join(format!("aws_iam_actions-{}.json", date));
+        std::fs::write(&archive_path, cached_data)?;
+        if let Some(source) = read_iam_actions_cache_source() {
+            std::fs::write(archive_path.with_extension("source"), source)?;
+        }
+        eprintln!(
+            "[*] Archived AWS IAM actions cache to {}",
+            archive_path.display()
+        );
+    }
     delete_iam_actions_cache()?;
-    retrieve_iam_actions_json()?;
-    println!("[*] Updated AWS IAM actions cache.");
+    fetch_iam_actions_json(url)?;
+    eprintln!("[*] Updated AWS IAM actions cache.");
     Ok(())
 }
+
+/// Reads and parses an IAM policy document from a local JSON or YAML file.
+///
+/// # Arguments
+///
+/// * `policy_file` - The path to the policy file.
+/// * `format` - The format of the policy file. If not provided, it is detected from the
+///   extension of the file, defaulting to JSON.
+///
+/// # Returns
+///
+/// A `Result` containing:
+/// - `Ok(PolicyFile)`: The parsed policy document, with its format and heading comments
+/// - `Err(Box<dyn std::error::Error>)`: An error if the file cannot be read or parsed
+///
+/// # Examples
+///
+/// ```no_run
+/// # use aws_iam_expansion::utils::read_policy_file;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let policy = read_policy_file("policy.yaml", None)?;
+/// println!("{} statement(s)", policy.document.statement.len());
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_policy_file(
+    policy_file: &str,
+    format: Option<crate::policy::PolicyFormat>,
+) -> Result<crate::policy::PolicyFile, Box<dyn std::error::Error>> {
+    let format = format
+        .or_else(|| crate::policy::PolicyFormat::from_extension(policy_file))
+        .unwrap_or(crate::policy::PolicyFormat::Json);
+    let policy_source = std::fs::read_to_string(policy_file)
+        .map_err(|error| format!("Could not read policy file '{}': {}", policy_file, error))?;
+    crate::policy::PolicyFile::parse(&policy_source, format)
+        .map_err(|error| format!("Could not parse policy file '{}': {}", policy_file, error).into())
+}
@@ -0,0 +1,376 @@
+//! End-to-end tests of the `aws-iam-expansion` binary.
+//!
+//! Each test runs the binary against the test fixture catalog, with `HOME` pointing at a
+//! scratch directory so the user's cache and configuration file are never touched.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// The catalog fixture the binary is run against.
+const FIXTURE_CATALOG: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/catalog/fixture_aws_iam_actions.json"
+);
+
+/// Creates an empty scratch directory for a test.
+///
+/// # Arguments
+///
+/// * `test` - The name of the test, keeping the directories of concurrent tests apart.
+fn scratch_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "aws_iam_expansion-cli-{}-{}",
+        test,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs the binary with the given arguments, loading the fixture catalog as a local source.
+///
+/// # Arguments
+///
+/// * `home` - The directory used as `HOME`.
+/// * `args` - The command-line arguments.
+fn run(home: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_aws-iam-expansion"))
+        .env("HOME", home)
+        .args([
+            "--catalog-source",
+            "local",
+            "--catalog-path",
+            FIXTURE_CATALOG,
+        ])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+/// Parses the standard output of a run as JSON.
+fn stdout_json(output: &Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|error| {
+        panic!(
+            "stdout is not JSON ({}): {}",
+            error,
+            String::from_utf8_lossy(&output.stdout)
+        )
+    })
+}
+
+#[test]
+fn describe_action_json_output_parses() {
+    let home = scratch_dir("describe-action");
+    let output = run(
+        &home,
+        &["describe-action", "iam:PassRole", "--format", "json"],
+    );
+
+    let descriptions = stdout_json(&output);
+    assert_eq!(descriptions[0]["action"], "iam:PassRole");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[*] Loading AWS IAM actions"));
+}
+
+#[test]
+fn describe_action_json_output_parses_from_the_cache() {
+    let home = scratch_dir("describe-action-cache");
+    let cache_dir = home.join(".cache/aws_iam_expansion");
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    std::fs::copy(FIXTURE_CATALOG, cache_dir.join("aws_iam_actions.json")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aws-iam-expansion"))
+        .env("HOME", &home)
+        .args([
+            "--offline",
+            "describe-action",
+            "s3:Get*",
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert_eq!(stdout_json(&output)[0]["action"], "s3:GetObject");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[*] Using cached"));
+}
+
+#[test]
+fn catalog_diff_json_output_parses() {
+    let home = scratch_dir("catalog-diff");
+    let old_catalog = home.join("old.json");
+    std::fs::write(
+        &old_catalog,
+        r#"[{
+            "service": "AWS Security Token Service",
+            "servicePrefix": "sts",
+            "actions": [{ "action": "sts:AssumeRole", "type": "Write" }]
+        }]"#,
+    )
+    .unwrap();
+
+    let output = run(
+        &home,
+        &[
+            "catalog-diff",
+            "--old",
+            old_catalog.to_str().unwrap(),
+            "--format",
+            "json",
+        ],
+    );
+
+    let diff = stdout_json(&output);
+    assert_eq!(diff["added_services"], serde_json::json!(["iam", "s3"]));
+    assert_eq!(
+        diff["services"][2]["added_actions"][0]["action"],
+        "sts:GetCallerIdentity"
+    );
+}
+
+#[test]
+fn drift_json_output_parses() {
+    let home = scratch_dir("drift");
+    let old_catalog = home.join("old.json");
+    std::fs::write(
+        &old_catalog,
+        r#"[{
+            "service": "Amazon S3",
+            "servicePrefix": "s3",
+            "actions": [{ "action": "s3:GetObject", "type": "Read" }]
+        }]"#,
+    )
+    .unwrap();
+    let policy_file = home.join("policy.json");
+    std::fs::write(
+        &policy_file,
+        r#"{
+            "Version": "2012-10-17",
+            "Statement": [{ "Effect": "Allow", "Action": "s3:*", "Resource": "*" }]
+        }"#,
+    )
+    .unwrap();
+
+    let output = run(
+        &home,
+        &[
+            "drift",
+            "--policy-file",
+            policy_file.to_str().unwrap(),
+            "--old",
+            old_catalog.to_str().unwrap(),
+            "--format",
+            "json",
+        ],
+    );
+
+    let drift = stdout_json(&output);
+    assert_eq!(drift.as_array().unwrap().len(), 1);
+    assert_eq!(drift[0]["effect"], "Allow");
+    assert_eq!(
+        drift[0]["patterns"][0]["patterns"],
+        serde_json::json!(["s3:*"])
+    );
+}
+
+#[test]
+fn scan_template_json_output_parses() {
+    let home = scratch_dir("scan-template");
+    let template = home.join("stack.yaml");
+    std::fs::write(
+        &template,
+        r#"
+Resources:
+  AppRole:
+    Type: AWS::IAM::Role
+    Properties:
+      Policies:
+        - PolicyName: Artifacts
+          PolicyDocument:
+            Statement:
+              - Effect: Allow
+                Action: "s3:Get*"
+                Resource: "*"
+  Queue:
+    Type: AWS::SQS::Queue
+"#,
+    )
+    .unwrap();
+    let empty_template = home.join("empty.yaml");
+    std::fs::write(&empty_template, "Resources: {}\n").unwrap();
+
+    let output = run(
+        &home,
+        &[
+            "scan-template",
+            "--template",
+            template.to_str().unwrap(),
+            "--template",
+            empty_template.to_str().unwrap(),
+            "--format",
+            "json",
+        ],
+    );
+
+    let scanned = stdout_json(&output);
+    assert_eq!(scanned.as_array().unwrap().len(), 1);
+    assert_eq!(
+        scanned[0]["document"]["Statement"][0]["Action"],
+        serde_json::json!(["s3:GetObject"])
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No policy documents found"));
+}
+
+#[test]
+fn scan_plan_json_output_parses() {
+    let home = scratch_dir("scan-plan");
+    let plan = home.join("plan.json");
+    std::fs::write(
+        &plan,
+        serde_json::json!({
+            "format_version": "1.2",
+            "planned_values": {
+                "root_module": {
+                    "resources": [
+                        {
+                            "address": "aws_iam_policy.app",
+                            "type": "aws_iam_policy",
+                            "values": {
+                                "policy": r#"{"Statement": [{"Effect": "Allow", "Action": "sts:*", "Resource": "*"}]}"#
+                            }
+                        },
+                        {
+                            "address": "aws_iam_policy.dynamic",
+                            "type": "aws_iam_policy",
+                            "values": {}
+                        }
+                    ]
+                }
+            },
+            "resource_changes": [
+                {
+                    "address": "aws_iam_policy.dynamic",
+                    "type": "aws_iam_policy",
+                    "change": { "actions": ["create"], "after_unknown": { "policy": true } }
+                }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let output = run(
+        &home,
+        &[
+            "scan-plan",
+            "--plan",
+            plan.to_str().unwrap(),
+            "--format",
+            "json",
+        ],
+    );
+
+    let scanned = stdout_json(&output);
+    assert_eq!(scanned.as_array().unwrap().len(), 1);
+    assert_eq!(
+        scanned[0]["document"]["Statement"][0]["Action"],
+        serde_json::json!(["sts:AssumeRole", "sts:GetCallerIdentity"])
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("only known after apply"));
+}
+
+#[test]
+fn list_services_counts_access_level_spellings_together() {
+    let home = scratch_dir("list-services");
+    let catalog = home.join("catalog.json");
+    std::fs::write(
+        &catalog,
+        r#"[{
+            "service": "AWS Identity and Access Management (IAM)",
+            "servicePrefix": "iam",
+            "actions": [
+                { "action": "iam:AttachRolePolicy", "type": "Permissions management" },
+                { "action": "iam:PutRolePolicy", "type": "permissions-management" },
+                { "action": "iam:GetRole", "type": "Read" }
+            ]
+        }]"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aws-iam-expansion"))
+        .env("HOME", &home)
+        .args([
+            "--catalog-source",
+            "local",
+            "--catalog-path",
+            catalog.to_str().unwrap(),
+            "list-services",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("(3 action(s): 1 Read, 2 Permissions management)")
+    );
+}
+
+#[test]
+fn cache_commands_do_not_load_the_catalog() {
+    let home = scratch_dir("delete-cache");
+    let output = Command::new(env!("CARGO_BIN_EXE_aws-iam-expansion"))
+        .env("HOME", &home)
+        .args([
+            "--catalog-source",
+            "local",
+            "--catalog-path",
+            home.join("missing.json").to_str().unwrap(),
+            "delete-cache",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Loading"));
+}
+
+#[test]
+fn offline_runs_use_a_cache_fetched_from_another_url() {
+    let home = scratch_dir("cache-source");
+    let cache_dir = home.join(".cache/aws_iam_expansion");
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    std::fs::copy(FIXTURE_CATALOG, cache_dir.join("aws_iam_actions.json")).unwrap();
+    std::fs::write(
+        cache_dir.join("aws_iam_actions.source"),
+        "https://mirror.example.com/iam.json",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aws-iam-expansion"))
+        .env("HOME", &home)
+        .args([
+            "--offline",
+            "describe-action",
+            "sts:AssumeRole",
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert_eq!(stdout_json(&output)[0]["action"], "sts:AssumeRole");
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("was fetched from https://mirror.example.com/iam.json")
+    );
+}